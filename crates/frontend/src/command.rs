@@ -1 +1,11 @@
-pub mod joints;
\ No newline at end of file
+pub(crate) mod arm;
+pub(crate) mod control_loop;
+pub mod joints;
+pub(crate) mod kinematics;
+pub(crate) mod latency;
+pub(crate) mod oscilloscope;
+pub(crate) mod recording;
+pub(crate) mod scheme;
+pub(crate) mod speed_mode;
+pub(crate) mod trajectory;
+pub(crate) mod velocity;
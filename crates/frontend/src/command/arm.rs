@@ -0,0 +1,640 @@
+use std::fmt::{Display, Formatter};
+
+use egui::{DragValue, RichText};
+
+use crate::command::kinematics::{Chain, IkError, JogFrame, Pose, SINGULARITY_THRESHOLD};
+use crate::command::trajectory::JointTrajectory;
+use crate::input::action::POSE_SLOT_ACTIONS;
+use crate::robot::profile::{JointPose, JointProfile, RobotProfile};
+
+#[derive(Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub(crate) enum ArmMode {
+    // inputs command joint velocities.
+    #[default]
+    Velocity,
+    // axis deflection maps onto the joint range, buttons jog the target.
+    Position,
+    // sticks jog the end effector, joint positions come from inverse kinematics.
+    Cartesian,
+}
+
+impl Display for ArmMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArmMode::Velocity => write!(f, "Velocity Jog"),
+            ArmMode::Position => write!(f, "Absolute Position"),
+            ArmMode::Cartesian => write!(f, "Cartesian Jog"),
+        }
+    }
+}
+
+/// Operator input for a single joint in [-1, 1].
+#[derive(Clone, Copy, Default)]
+pub(crate) struct JointInput {
+    pub(crate) value: f64,
+    // comes from a single axis, so in position mode it maps onto the joint range.
+    pub(crate) absolute: bool,
+}
+
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub(crate) struct ArmJoint {
+    // commanded position, in the joint's unit.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) position: f64,
+    // commanded velocity, in the joint's unit per second.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) velocity: f64,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    target: f64,
+}
+
+/// End effector jogging, inputs are X, Y, Z, roll, pitch and yaw rates in [-1, 1].
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub(crate) struct CartesianJog {
+    frame: JogFrame,
+    // m/s.
+    max_linear_speed: f64,
+    // rad/s.
+    max_angular_speed: f64,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    target: Option<Pose>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    status: Option<IkError>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    manipulability: f64,
+}
+
+impl Default for CartesianJog {
+    fn default() -> Self {
+        Self {
+            frame: JogFrame::World,
+            max_linear_speed: 0.1,
+            max_angular_speed: 0.5,
+            target: None,
+            status: None,
+            manipulability: 0f64,
+        }
+    }
+}
+
+impl CartesianJog {
+    /// Returns the joint positions for the jogged target, or keeps the current ones if it can't be reached.
+    fn step(&mut self, chain: &Chain, positions: &[f64], input: [f64; 6], dt: f64) -> Vec<f64> {
+        self.manipulability = chain.manipulability(positions);
+
+        let current: Pose = *self.target.get_or_insert_with(|| chain.forward(positions));
+        if input.iter().all(|value| *value == 0f64) {
+            return positions.to_vec();
+        }
+
+        let linear: [f64; 3] =
+            [input[0], input[1], input[2]].map(|v| v * self.max_linear_speed * dt);
+        let angular: [f64; 3] =
+            [input[3], input[4], input[5]].map(|v| v * self.max_angular_speed * dt);
+        let desired: Pose = current.displaced(linear, angular, self.frame);
+
+        match chain.inverse(&desired, positions) {
+            Ok(solution) => {
+                self.status = None;
+                self.target = Some(if chain.is_planar() {
+                    // only X, Y and yaw are controllable, drop the rest of the request.
+                    chain.forward(&solution)
+                } else {
+                    desired
+                });
+                solution
+            }
+            Err(error) => {
+                self.status = Some(error);
+                positions.to_vec()
+            }
+        }
+    }
+
+    fn merge(&mut self, before: &CartesianJog, edited: &CartesianJog) {
+        if edited.frame != before.frame {
+            self.frame = edited.frame;
+        }
+        if edited.max_linear_speed != before.max_linear_speed {
+            self.max_linear_speed = edited.max_linear_speed;
+        }
+        if edited.max_angular_speed != before.max_angular_speed {
+            self.max_angular_speed = edited.max_angular_speed;
+        }
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, chain: &Chain, positions: &[f64]) {
+        ui.horizontal(|ui| {
+            ui.label("Frame");
+            for frame in [JogFrame::World, JogFrame::Tool] {
+                ui.selectable_value(&mut self.frame, frame, frame.to_string());
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Speed");
+            ui.add(
+                DragValue::new(&mut self.max_linear_speed)
+                    .speed(0.01)
+                    .clamp_range(0.0..=f64::MAX)
+                    .suffix(" m/s"),
+            );
+            ui.add(
+                DragValue::new(&mut self.max_angular_speed)
+                    .speed(0.01)
+                    .clamp_range(0.0..=f64::MAX)
+                    .suffix(" rad/s"),
+            );
+        });
+
+        let pose: Pose = chain.forward(positions);
+        let [x, y, z] = pose.position;
+        let [roll, pitch, yaw] = pose.roll_pitch_yaw();
+
+        egui::Grid::new("cartesian_jog_grid")
+            .striped(true)
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("Solver");
+                ui.label(chain.solver().to_string());
+                ui.end_row();
+
+                ui.label("Position");
+                ui.monospace(format!("x {x:+.3}  y {y:+.3}  z {z:+.3} m"));
+                ui.end_row();
+
+                ui.label("Orientation");
+                ui.monospace(format!("r {roll:+.3}  p {pitch:+.3}  y {yaw:+.3} rad"));
+                ui.end_row();
+
+                ui.label("Manipulability");
+                ui.monospace(format!("{:.5}", self.manipulability));
+                ui.end_row();
+            });
+
+        if chain.is_planar() {
+            ui.label("Planar arm, Z, roll and pitch inputs are ignored.");
+        }
+        if self.manipulability < SINGULARITY_THRESHOLD {
+            ui.colored_label(ui.visuals().warn_fg_color, "⚠ Near a singularity");
+        }
+        if let Some(status) = &self.status {
+            ui.colored_label(ui.visuals().error_fg_color, format!("⛔ {status}"));
+        }
+    }
+}
+
+/// What the arm UI asks for, carried out on the control loop's state.
+pub(crate) enum ArmRequest {
+    Recall(JointPose),
+    Stop,
+}
+
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub(crate) struct ArmState {
+    pub(crate) mode: ArmMode,
+    pub(crate) joints: Vec<ArmJoint>,
+    pub(crate) cartesian: CartesianJog,
+    // pose being recalled, any operator input interrupts it.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    recall: Option<JointTrajectory>,
+    // pose slots pressed but not yet matched against the profile's poses.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pressed: Vec<usize>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    new_pose_name: String,
+}
+
+impl ArmState {
+    pub(crate) fn press_slot(&mut self, slot: usize) {
+        self.pressed.push(slot);
+    }
+
+    /// Whether a joint is being commanded to move.
+    pub(crate) fn moving(&self) -> bool {
+        self.recall.is_some() || self.joints.iter().any(|joint| joint.velocity != 0f64)
+    }
+
+    /// Holds every joint where it is and drops any pending recall.
+    pub(crate) fn halt(&mut self) {
+        self.recall = None;
+        self.pressed.clear();
+        self.cartesian.target = None;
+        for joint in self.joints.iter_mut() {
+            joint.velocity = 0f64;
+            joint.target = joint.position;
+        }
+    }
+
+    /// Keeps one teleop slot per profile joint, new joints start centered within their limits.
+    pub(crate) fn sync(&mut self, profiles: &[JointProfile]) {
+        self.joints.truncate(profiles.len());
+        while self.joints.len() < profiles.len() {
+            let index: usize = self.joints.len();
+            let position: f64 = profiles[index].clamp(0f64);
+            self.joints.push(ArmJoint {
+                position,
+                target: position,
+                ..Default::default()
+            });
+        }
+    }
+
+    fn positions(&self) -> Vec<f64> {
+        self.joints.iter().map(|joint| joint.position).collect()
+    }
+
+    fn recall(&mut self, pose: &JointPose, profiles: &[JointProfile]) {
+        let start: Vec<f64> = self.positions();
+        // joints missing from the pose hold their position.
+        let goal: Vec<f64> = profiles
+            .iter()
+            .zip(&start)
+            .enumerate()
+            .map(|(index, (profile, position))| {
+                profile.clamp(pose.positions.get(index).copied().unwrap_or(*position))
+            })
+            .collect();
+        let max_velocities: Vec<f64> = profiles.iter().map(|p| p.max_velocity).collect();
+
+        self.recall = Some(JointTrajectory::new(
+            &pose.name,
+            start,
+            goal,
+            &max_velocities,
+        ));
+    }
+
+    /// Advances commanded positions by `dt` seconds and clamps them at the soft limits.
+    pub(crate) fn step(
+        &mut self,
+        profile: &RobotProfile,
+        inputs: &[JointInput],
+        cartesian: [f64; 6],
+        dt: f64,
+    ) {
+        let profiles: &[JointProfile] = &profile.joints;
+        self.sync(profiles);
+
+        for slot in std::mem::take(&mut self.pressed) {
+            if let Some(pose) = profile.poses.iter().find(|p| p.slot == Some(slot)) {
+                self.recall(pose, profiles);
+            }
+        }
+
+        let operator_input: bool = cartesian.iter().any(|value| *value != 0f64)
+            || inputs.iter().any(|input| input.value != 0f64);
+        if operator_input {
+            self.recall = None;
+        }
+
+        if let Some(recall) = &mut self.recall {
+            let positions: Vec<f64> = recall.advance(dt);
+            for ((joint, profile), position) in self.joints.iter_mut().zip(profiles).zip(positions)
+            {
+                // never faster than the joint allows, even if the trajectory is ahead of it.
+                let max_step: f64 = profile.max_velocity.max(0f64) * dt;
+                let next: f64 = profile
+                    .clamp(joint.position + (position - joint.position).clamp(-max_step, max_step));
+                joint.velocity = if dt > 0f64 {
+                    (next - joint.position) / dt
+                } else {
+                    0f64
+                };
+                joint.position = next;
+                joint.target = next;
+            }
+
+            if recall.finished() {
+                self.recall = None;
+            }
+            self.cartesian.target = None;
+            return;
+        }
+
+        if self.mode != ArmMode::Cartesian {
+            self.cartesian.target = None;
+        }
+        let solution: Vec<f64> = if self.mode == ArmMode::Cartesian {
+            self.cartesian
+                .step(&Chain::new(profiles), &self.positions(), cartesian, dt)
+        } else {
+            Vec::new()
+        };
+
+        let mut limited: bool = false;
+        for (index, (joint, profile)) in self.joints.iter_mut().zip(profiles).enumerate() {
+            let input: JointInput = inputs.get(index).copied().unwrap_or_default();
+            let value: f64 = input.value.clamp(-1f64, 1f64);
+            let max_step: f64 = profile.max_velocity * dt;
+
+            let next: f64 = match self.mode {
+                ArmMode::Position if input.absolute => {
+                    joint.target = profile.min_position
+                        + (value + 1f64) / 2f64 * (profile.max_position - profile.min_position);
+                    joint.position + (joint.target - joint.position).clamp(-max_step, max_step)
+                }
+                ArmMode::Position => {
+                    joint.target = profile.clamp(joint.target + value * max_step);
+                    joint.position + (joint.target - joint.position).clamp(-max_step, max_step)
+                }
+                ArmMode::Velocity => joint.position + value * max_step,
+                ArmMode::Cartesian => {
+                    let goal: f64 = solution.get(index).copied().unwrap_or(joint.position);
+                    limited |= (goal - joint.position).abs() > max_step;
+                    joint.position + (goal - joint.position).clamp(-max_step, max_step)
+                }
+            };
+
+            let next: f64 = profile.clamp(next);
+            joint.velocity = if dt > 0f64 {
+                (next - joint.position) / dt
+            } else {
+                0f64
+            };
+            joint.position = next;
+            if self.mode != ArmMode::Position {
+                joint.target = next;
+            }
+        }
+
+        // the joints could not follow at their velocity limits, restart from where they are.
+        if limited {
+            self.cartesian.target = None;
+        }
+    }
+
+    /// Takes what the UI changed on a copy drawn `before`, the commanded joints stay the loop's.
+    pub(crate) fn merge(&mut self, before: &ArmState, edited: ArmState) {
+        if edited.mode != before.mode {
+            self.mode = edited.mode;
+        }
+        self.cartesian.merge(&before.cartesian, &edited.cartesian);
+        self.new_pose_name = edited.new_pose_name;
+    }
+
+    pub(crate) fn request(&mut self, request: ArmRequest, profile: &RobotProfile) {
+        match request {
+            ArmRequest::Recall(pose) => self.recall(&pose, &profile.joints),
+            ArmRequest::Stop => self.recall = None,
+        }
+    }
+
+    pub(crate) fn ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        profile: &mut RobotProfile,
+    ) -> Option<ArmRequest> {
+        let profiles: &[JointProfile] = &profile.joints;
+        self.sync(profiles);
+
+        ui.horizontal(|ui| {
+            ui.label("Mode");
+            for mode in [ArmMode::Velocity, ArmMode::Position, ArmMode::Cartesian] {
+                ui.selectable_value(&mut self.mode, mode, mode.to_string());
+            }
+        });
+
+        ui.separator();
+
+        if profiles.is_empty() {
+            ui.label("The robot profile has no joints.");
+            return None;
+        }
+
+        if self.mode == ArmMode::Cartesian {
+            let positions: Vec<f64> = self.positions();
+            self.cartesian.ui(ui, &Chain::new(profiles), &positions);
+
+            ui.separator();
+        }
+
+        egui::Grid::new("arm_joints_grid")
+            .striped(true)
+            .num_columns(2)
+            .show(ui, |ui| {
+                for (joint, profile) in self.joints.iter().zip(profiles) {
+                    ui.label(&profile.name);
+
+                    let span: f64 = profile.max_position - profile.min_position;
+                    let fraction: f32 = if span > 0f64 {
+                        ((joint.position - profile.min_position) / span) as f32
+                    } else {
+                        0f32
+                    };
+                    let unit: &str = profile.kind.unit();
+                    let at_limit: bool = joint.position <= profile.min_position
+                        || joint.position >= profile.max_position;
+                    let mut text: RichText = RichText::new(format!(
+                        "{:+.3} {unit}  [{:+.2}, {:+.2}]  {:+.2} {unit}/s",
+                        joint.position, profile.min_position, profile.max_position, joint.velocity,
+                    ))
+                    .monospace();
+                    if at_limit {
+                        text = text.color(ui.visuals().warn_fg_color);
+                    }
+                    ui.add(
+                        egui::widgets::ProgressBar::new(fraction)
+                            .desired_width(300f32)
+                            .text(text),
+                    );
+                    ui.end_row();
+                }
+            });
+
+        ui.separator();
+
+        self.poses_ui(ui, profile)
+    }
+
+    fn poses_ui(&mut self, ui: &mut egui::Ui, profile: &mut RobotProfile) -> Option<ArmRequest> {
+        ui.heading("📌 Poses");
+
+        let mut request: Option<ArmRequest> = None;
+        if let Some(recall) = &self.recall {
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::widgets::ProgressBar::new(recall.progress() as f32)
+                        .desired_width(200f32)
+                        .text(format!("{} {:.1} s", recall.name, recall.remaining())),
+                );
+                if ui.button("⏹ Stop").clicked() {
+                    request = Some(ArmRequest::Stop);
+                }
+            });
+        }
+
+        let mut recalled: Option<usize> = None;
+        let mut captured: Option<usize> = None;
+        let mut removed: Option<usize> = None;
+        egui::Grid::new("arm_poses_grid")
+            .striped(true)
+            .num_columns(4)
+            .show(ui, |ui| {
+                for (index, pose) in profile.poses.iter_mut().enumerate() {
+                    ui.add(egui::TextEdit::singleline(&mut pose.name).desired_width(100f32));
+                    let selected: String = match pose.slot {
+                        Some(slot) => format!("Slot {}", slot + 1),
+                        None => "None".to_string(),
+                    };
+                    egui::ComboBox::from_id_source(("pose_slot", index))
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut pose.slot, None, "None");
+                            for slot in 0..POSE_SLOT_ACTIONS {
+                                let text: String = format!("Slot {}", slot + 1);
+                                ui.selectable_value(&mut pose.slot, Some(slot), text);
+                            }
+                        });
+                    ui.monospace(
+                        pose.positions
+                            .iter()
+                            .map(|position| format!("{position:+.2}"))
+                            .collect::<Vec<String>>()
+                            .join(" "),
+                    );
+                    ui.horizontal(|ui| {
+                        if ui.button("▶ Go").clicked() {
+                            recalled = Some(index);
+                        }
+                        if ui.button("📷 Update").clicked() {
+                            captured = Some(index);
+                        }
+                        if ui.small_button("🗑").clicked() {
+                            removed = Some(index);
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+
+        if let Some(index) = recalled {
+            request = Some(ArmRequest::Recall(profile.poses[index].clone()));
+        }
+        if let Some(index) = captured {
+            profile.poses[index].positions = self.positions();
+        }
+        if let Some(index) = removed {
+            profile.poses.remove(index);
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.new_pose_name)
+                    .hint_text("Pose name")
+                    .desired_width(150f32),
+            );
+            if ui
+                .add_enabled(
+                    !self.new_pose_name.is_empty(),
+                    egui::Button::new("📷 Capture"),
+                )
+                .clicked()
+            {
+                profile.poses.push(JointPose {
+                    name: std::mem::take(&mut self.new_pose_name),
+                    positions: self.positions(),
+                    slot: None,
+                });
+            }
+        });
+
+        request
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use super::*;
+
+    fn arm(mode: ArmMode, profiles: &[JointProfile]) -> ArmState {
+        let mut arm: ArmState = ArmState {
+            mode,
+            ..ArmState::default()
+        };
+        arm.sync(profiles);
+        arm
+    }
+
+    // the first joint on an axis, the second on a pair of buttons.
+    fn inputs(axis: f64, buttons: f64) -> [JointInput; 2] {
+        [
+            JointInput {
+                value: axis,
+                absolute: true,
+            },
+            JointInput {
+                value: buttons,
+                absolute: false,
+            },
+        ]
+    }
+
+    #[test]
+    fn velocity_jog_stops_at_the_soft_limit() {
+        let profile: RobotProfile = RobotProfile::default();
+        let mut arm: ArmState = arm(ArmMode::Velocity, &profile.joints);
+
+        arm.step(&profile, &inputs(1.0, 0.0), [0f64; 6], 0.5);
+        assert_eq!(arm.joints[0].position, 0.5);
+        assert_eq!(arm.joints[0].velocity, 1.0);
+        for _ in 0..4 {
+            arm.step(&profile, &inputs(1.0, 0.0), [0f64; 6], 0.5);
+        }
+        assert_eq!(arm.joints[0].position, FRAC_PI_2);
+        assert_eq!(arm.joints[0].velocity, 0.0);
+    }
+
+    #[test]
+    fn position_mode_maps_the_axis_onto_the_range_at_max_velocity() {
+        let profile: RobotProfile = RobotProfile::default();
+        let mut arm: ArmState = arm(ArmMode::Position, &profile.joints);
+
+        arm.step(&profile, &inputs(-1.0, 0.0), [0f64; 6], 0.1);
+        assert_eq!(arm.joints[0].position, -0.1);
+        for _ in 0..20 {
+            arm.step(&profile, &inputs(-1.0, 0.0), [0f64; 6], 0.1);
+        }
+        assert_eq!(arm.joints[0].position, -FRAC_PI_2);
+    }
+
+    #[test]
+    fn position_mode_buttons_jog_the_target() {
+        let profile: RobotProfile = RobotProfile::default();
+        let mut arm: ArmState = arm(ArmMode::Position, &profile.joints);
+        arm.step(&profile, &inputs(0.0, 1.0), [0f64; 6], 0.25);
+        arm.step(&profile, &inputs(0.0, 0.0), [0f64; 6], 0.25);
+
+        assert_eq!(arm.joints[1].position, 0.25);
+        assert_eq!(arm.joints[1].target, 0.25);
+    }
+
+    #[test]
+    fn pose_slots_recall_without_moving_locked_joints() {
+        let mut profile: RobotProfile = RobotProfile::default();
+        profile.joints[1].max_velocity = 0f64;
+        let mut arm: ArmState = arm(ArmMode::Velocity, &profile.joints);
+
+        // stow is on the first slot.
+        arm.press_slot(0);
+        for _ in 0..8 {
+            arm.step(&profile, &[], [0f64; 6], 0.25);
+            for (joint, profile) in arm.joints.iter().zip(&profile.joints) {
+                assert!(joint.velocity.abs() <= profile.max_velocity);
+            }
+        }
+
+        assert!(!arm.moving());
+        assert_eq!(arm.joints[0].position, -1.5);
+        assert_eq!(arm.joints[1].position, 0.0);
+    }
+}
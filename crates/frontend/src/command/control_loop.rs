@@ -0,0 +1,430 @@
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, TryLockError, Weak};
+use std::time::{Duration, SystemTime};
+
+use egui::DragValue;
+use gilrs_core::utils::time_now;
+
+use crate::command::joints::JointState;
+use crate::command::latency::{Latency, Stage};
+use crate::command::oscilloscope::Oscilloscope;
+use crate::command::velocity::{DriveCommand, Odometry, WATCHDOG};
+use crate::event_log::{EventLog, LogKind, Severity};
+use crate::gamepad::control_panel::GamepadControlPanel;
+use crate::input::role::Role;
+use crate::robot::profile::RobotProfile;
+use crate::wasm::repaint::Repainter;
+
+// longest step that is integrated at once, after the loop was suspended the arm must not jump.
+const MAX_DT: f64 = 0.1;
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub(crate) struct LoopSettings {
+    // Hz.
+    rate: f64,
+}
+
+impl Default for LoopSettings {
+    fn default() -> Self {
+        Self { rate: 100.0 }
+    }
+}
+
+impl LoopSettings {
+    fn period(&self) -> Duration {
+        Duration::from_secs_f64(1f64 / self.rate.max(1f64))
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, stats: &LoopStats) {
+        egui::Grid::new("control_loop_grid")
+            .striped(true)
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("Rate");
+                ui.add(
+                    DragValue::new(&mut self.rate)
+                        .speed(1.0)
+                        .clamp_range(1.0..=1000.0)
+                        .suffix(" Hz"),
+                );
+                ui.end_row();
+
+                ui.label("Measured");
+                ui.monospace(if stats.period > 0f64 {
+                    format!("{:.1} Hz", 1f64 / stats.period)
+                } else {
+                    "-".to_string()
+                });
+                ui.end_row();
+
+                ui.label("Step Time");
+                ui.monospace(format!("{:.3} ms", stats.step_time * 1000f64));
+                ui.end_row();
+
+                ui.label("Steps");
+                ui.monospace(stats.steps.to_string());
+                ui.end_row();
+
+                ui.label("Overruns");
+                ui.monospace(stats.overruns.to_string());
+                ui.end_row();
+            });
+    }
+}
+
+/// Timing of the last steps, to tell whether the loop keeps up with its rate.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct LoopStats {
+    steps: u64,
+    // steps that started later than a whole period after the previous one.
+    overruns: u64,
+    // seconds, exponential moving averages.
+    period: f64,
+    step_time: f64,
+    last_step: Option<SystemTime>,
+}
+
+impl LoopStats {
+    /// Returns the seconds since the previous step.
+    fn start(&mut self, now: SystemTime, settings: &LoopSettings) -> f64 {
+        let nominal: f64 = settings.period().as_secs_f64();
+        let dt: f64 = self
+            .last_step
+            .and_then(|last| now.duration_since(last).ok())
+            .map(|elapsed| elapsed.as_secs_f64())
+            .unwrap_or(nominal);
+        self.last_step = Some(now);
+
+        if dt > 2f64 * nominal {
+            self.overruns += 1;
+        }
+        self.period = smooth(self.period, dt, self.steps);
+        self.steps += 1;
+        dt
+    }
+
+    fn finish(&mut self, started: SystemTime) {
+        let elapsed: f64 = time_now()
+            .duration_since(started)
+            .unwrap_or_default()
+            .as_secs_f64();
+        self.step_time = smooth(self.step_time, elapsed, self.steps);
+    }
+}
+
+fn smooth(average: f64, sample: f64, samples: u64) -> f64 {
+    if samples == 0 {
+        sample
+    } else {
+        average + (sample - average) * 0.05
+    }
+}
+
+/// Everything the control loop owns, the UI copies what it shows and writes edits back.
+#[derive(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub(crate) struct Controller {
+    pub(crate) settings: LoopSettings,
+    pub(crate) gamepad_control_panel: Shared<GamepadControlPanel>,
+    pub(crate) joints: JointState,
+    pub(crate) robot_profile: RobotProfile,
+    pub(crate) event_log: Shared<EventLog>,
+    pub(crate) oscilloscope: Shared<Oscilloscope>,
+    pub(crate) latency: Shared<Latency>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) drive_command: DriveCommand,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) odometry: Odometry,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    stats: LoopStats,
+    // redraws the UI when something it shows changed.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) repainter: Repainter,
+    // safety state at the previous step, changes are logged.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    safety: (bool, bool, BTreeSet<Role>),
+}
+
+impl Controller {
+    /// Polls the gamepads and produces the next commands.
+    fn step(&mut self) {
+        let now: SystemTime = time_now();
+        let overruns: u64 = self.stats.overruns;
+        let dt: f64 = self.stats.start(now, &self.settings);
+        // the last command already expired, like in a hidden browser tab, don't act on stale input.
+        let starved: bool = dt > WATCHDOG.as_secs_f64();
+        if self.stats.overruns > overruns {
+            self.event_log.push(
+                now,
+                "Control Loop",
+                Severity::Warning,
+                LogKind::Telemetry,
+                format!(
+                    "Step came {:.1} ms after the previous one{}",
+                    dt * 1000f64,
+                    if starved { ", drive output zeroed" } else { "" }
+                ),
+            );
+        }
+
+        // while the UI draws the panel, polling waits for the next step instead of the UI.
+        let received: bool = self
+            .gamepad_control_panel
+            .try_with(|panel| panel.update(&mut self.joints, &mut self.event_log, now))
+            .unwrap_or_default();
+        let arrivals: Vec<SystemTime> = std::mem::take(&mut self.joints.arrivals);
+        let polled: SystemTime = time_now();
+        self.latency.record(&arrivals, Stage::Received, polled);
+        self.joints.tick(polled);
+        self.latency.record(&arrivals, Stage::Filtered, time_now());
+        self.log_safety(now);
+        let previous: (f64, f64) = self.drive_command.normalized;
+        let mut output: (f64, f64) = self.joints.drive_output();
+        if starved {
+            output = (0f64, 0f64);
+        }
+        self.latency.record(&arrivals, Stage::Mixed, time_now());
+        self.oscilloscope.sample(now, &self.joints, output);
+        self.drive_command = DriveCommand::new(output, &self.robot_profile.drive, now);
+        self.latency.record(&arrivals, Stage::Commanded, time_now());
+        if std::mem::take(&mut self.joints.reset_odometry) {
+            self.odometry = Odometry::default();
+            self.event_log.push(
+                now,
+                "Joints",
+                Severity::Info,
+                LogKind::Safety,
+                "Odometry reset",
+            );
+        }
+        self.odometry
+            .integrate(self.drive_command.twist, dt.min(MAX_DT));
+        self.joints.step_arm(&self.robot_profile, dt.min(MAX_DT));
+
+        if received || self.drive_command.normalized != previous || self.joints.arm.moving() {
+            self.repainter.request(now);
+        }
+
+        self.stats.finish(now);
+    }
+
+    fn log_safety(&mut self, now: SystemTime) {
+        let (estop, disarmed, lost) = &self.safety;
+        if self.joints.estop != *estop {
+            let (severity, payload) = if self.joints.estop {
+                (Severity::Error, "E-stop latched")
+            } else {
+                (Severity::Info, "E-stop reset")
+            };
+            self.event_log
+                .push(now, "Joints", severity, LogKind::Safety, payload);
+        }
+        if self.joints.disarmed != *disarmed {
+            let payload: &str = if self.joints.disarmed {
+                "Disarmed, outputs zeroed"
+            } else {
+                "Armed"
+            };
+            self.event_log
+                .push(now, "Joints", Severity::Warning, LogKind::Safety, payload);
+        }
+        for role in self.joints.lost.difference(lost) {
+            self.event_log.push(
+                now,
+                role.to_string(),
+                Severity::Error,
+                LogKind::Safety,
+                "Controller lost, outputs zeroed",
+            );
+        }
+        for role in lost.difference(&self.joints.lost) {
+            self.event_log.push(
+                now,
+                role.to_string(),
+                Severity::Info,
+                LogKind::Safety,
+                "Controller re-acquired",
+            );
+        }
+        self.safety = (
+            self.joints.estop,
+            self.joints.disarmed,
+            self.joints.lost.clone(),
+        );
+    }
+}
+
+/// Runs the controller at a fixed rate, independent of how often the UI is drawn.
+///
+/// The UI never draws while holding the controller, so a slow frame can't hold up a step.
+#[derive(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub(crate) struct ControlLoop {
+    controller: Arc<Mutex<Controller>>,
+}
+
+impl ControlLoop {
+    /// The loop stops on its own once this is dropped.
+    pub(crate) fn start(&self) {
+        spawn(Arc::downgrade(&self.controller));
+    }
+
+    /// Copies what the UI shows out of the controller.
+    pub(crate) fn read<T>(&self, read: impl FnOnce(&Controller) -> T) -> T {
+        read(&lock(&self.controller))
+    }
+
+    /// Applies an edit or request from the UI.
+    pub(crate) fn write<T>(&self, write: impl FnOnce(&mut Controller) -> T) -> T {
+        write(&mut lock(&self.controller))
+    }
+
+    /// Draws part of the controller on a copy and writes the edit back.
+    ///
+    /// The edit is dropped if the loop changed the part meanwhile, the next frame shows its change.
+    pub(crate) fn edit<T: Clone + PartialEq, R>(
+        &self,
+        part: impl Fn(&mut Controller) -> &mut T,
+        draw: impl FnOnce(&mut T) -> R,
+    ) -> R {
+        let before: T = self.write(|controller| part(controller).clone());
+        let mut edited: T = before.clone();
+        let response: R = draw(&mut edited);
+        if edited != before {
+            self.write(|controller| {
+                let live: &mut T = part(controller);
+                if *live == before {
+                    *live = edited;
+                }
+            });
+        }
+        response
+    }
+
+    /// Draws a part the UI shares with the loop in place.
+    pub(crate) fn shared<T, R>(
+        &self,
+        part: impl FnOnce(&Controller) -> &Shared<T>,
+        draw: impl FnOnce(&mut T) -> R,
+    ) -> R {
+        let shared: Arc<Mutex<T>> = self.read(|controller| part(controller).inner.clone());
+        let mut shared: MutexGuard<T> = lock(&shared);
+        draw(&mut shared)
+    }
+
+    pub(crate) fn ui(&self, ui: &mut egui::Ui) {
+        let stats: LoopStats = self.read(|controller| controller.stats);
+        self.edit(
+            |controller| &mut controller.settings,
+            |settings| settings.ui(ui, &stats),
+        );
+    }
+}
+
+type Update<T> = Box<dyn FnOnce(&mut T) + Send>;
+
+/// Part of the controller the UI draws in place, the loop never waits for the UI to finish.
+#[derive(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub(crate) struct Shared<T> {
+    inner: Arc<Mutex<T>>,
+    // updates made while the UI was drawing, applied in order with the next one.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pending: Vec<Update<T>>,
+}
+
+impl<T> Shared<T> {
+    /// Applies `update` now, or once the UI is done drawing.
+    pub(crate) fn update(&mut self, update: impl FnOnce(&mut T) + Send + 'static) {
+        self.pending.push(Box::new(update));
+        if let Some(mut inner) = try_lock(&self.inner) {
+            for update in self.pending.drain(..) {
+                update(&mut inner);
+            }
+        }
+    }
+
+    /// Runs `with` unless the UI is drawing, then `None`.
+    pub(crate) fn try_with<R>(&self, with: impl FnOnce(&mut T) -> R) -> Option<R> {
+        try_lock(&self.inner).map(|mut inner| with(&mut inner))
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // a panicking step leaves the state usable, keep controlling.
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn try_lock<T>(mutex: &Mutex<T>) -> Option<MutexGuard<'_, T>> {
+    match mutex.try_lock() {
+        Ok(guard) => Some(guard),
+        Err(TryLockError::Poisoned(poisoned)) => Some(poisoned.into_inner()),
+        Err(TryLockError::WouldBlock) => None,
+    }
+}
+
+/// Steps once and returns how long to wait for the next step, `None` once the controller is gone.
+fn step(controller: &Weak<Mutex<Controller>>) -> Option<Duration> {
+    let controller: Arc<Mutex<Controller>> = controller.upgrade()?;
+    let mut controller: MutexGuard<Controller> = lock(&controller);
+    controller.step();
+    Some(controller.settings.period())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn spawn(controller: Weak<Mutex<Controller>>) {
+    use std::time::Instant;
+
+    std::thread::Builder::new()
+        .name("control_loop".to_string())
+        .spawn(move || {
+            let mut next: Instant = Instant::now();
+            while let Some(period) = step(&controller) {
+                next += period;
+                let now: Instant = Instant::now();
+                if next > now {
+                    std::thread::sleep(next - now);
+                } else {
+                    // fell behind, don't try to catch up with a burst of steps.
+                    next = now;
+                }
+            }
+        })
+        .expect("failed to spawn the control loop thread");
+}
+
+#[cfg(target_arch = "wasm32")]
+fn spawn(controller: Weak<Mutex<Controller>>) {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use wasm_bindgen::closure::Closure;
+    use wasm_bindgen::JsCast;
+
+    /// Timer callback that reschedules itself, shared so it can refer to itself.
+    type Callback = Rc<RefCell<Option<Closure<dyn FnMut()>>>>;
+
+    // browsers throttle timers in hidden tabs to a second or more, such late steps zero the drive.
+    let callback: Callback = Rc::new(RefCell::new(None));
+    let next: Callback = callback.clone();
+    *callback.borrow_mut() = Some(Closure::new(move || {
+        if let Some(period) = step(&controller) {
+            schedule(next.borrow().as_ref(), period);
+        }
+    }));
+    schedule(callback.borrow().as_ref(), Duration::ZERO);
+
+    fn schedule(callback: Option<&Closure<dyn FnMut()>>, period: Duration) {
+        if let (Some(window), Some(callback)) = (eframe::web_sys::window(), callback) {
+            let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(
+                callback.as_ref().unchecked_ref(),
+                period.as_millis() as i32,
+            );
+        }
+    }
+}
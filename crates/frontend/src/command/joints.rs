@@ -1,74 +1,515 @@
-use eframe::web_sys::js_sys::Math::{abs, atan2, cos, sin, sqrt};
-use gilrs::{Axis, Button, EventType};
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::SystemTime;
+
+use gilrs_core::utils::time_now;
+
+use crate::command::arm::{ArmState, JointInput};
+use crate::command::recording::{RecordedStep, Recorder, RecorderRequest};
+#[cfg(feature = "serde")]
+use crate::command::recording::Recording;
+use crate::command::scheme::{DriveScheme, DriveSchemes};
+use crate::command::speed_mode::SpeedModes;
+use crate::input::action::{Action, ARM_JOINT_ACTIONS};
+use crate::input::binding::{BindingState, Bindings, ControllerModel};
+use crate::input::event::InputEvent;
+use crate::input::filter::{AxisFilters, FilterStates};
+use crate::input::role::Role;
+use crate::input::snapshot::InputSnapshot;
+use crate::robot::profile::RobotProfile;
+
+/// Input of the controller holding a role.
+#[derive(Default)]
+pub(crate) struct RoleInput {
+    // every button and axis.
+    pub(crate) input: InputSnapshot,
+    // `input` after smoothing, actions read their values from it.
+    filtered: InputSnapshot,
+    filters: FilterStates,
+    bindings: BindingState,
+}
 
 #[derive(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub(crate) struct JointState {
-    // buttons.
-    start: f32,
-    select: f32,
-    // trigger.
-    left_trigger_1: f32,
-    left_trigger_2: f32,
-    right_trigger_1: f32,
-    right_trigger_2: f32,
-    // axis.
-    left_axis_x: f32,
-    left_axis_y: f32,
-    right_axis_x: f32,
-    right_axis_y: f32,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) driver: RoleInput,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) operator: RoleInput,
+    pub(crate) filters: AxisFilters,
+    // inputs to actions.
+    pub(crate) bindings: Bindings,
+    // input mapping.
+    pub(crate) schemes: DriveSchemes,
+    // output envelope.
+    pub(crate) speed_modes: SpeedModes,
+    // manipulator.
+    pub(crate) arm: ArmState,
+    // latched until reset from the UI, zeroes every output.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) estop: bool,
+    // outputs stay zero until armed again, unlike the e-stop this isn't a fault.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) disarmed: bool,
+    // set by the reset odometry action until the control loop takes it.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) reset_odometry: bool,
+    // roles whose controller disconnected, every output stays zero until they are re-acquired.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) lost: BTreeSet<Role>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) recorder: Recorder,
+    // live input while a recording replays, only watched for the e-stop.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    live: BTreeMap<Role, RoleInput>,
+    // settings swapped out for the recorded ones while a recording replays.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    before_replay: Option<Box<JointState>>,
+    // times of the events fed since the control loop last took them, for latency.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) arrivals: Vec<SystemTime>,
 }
 
 impl JointState {
-    pub(crate) fn update(&mut self, event_type: EventType) {
-        match event_type {
-            EventType::ButtonChanged(button, value, ..) => {
-                self.update_button(button, value);
+    pub(crate) fn role(&self, role: Role) -> &RoleInput {
+        match role {
+            Role::Driver => &self.driver,
+            Role::Operator => &self.operator,
+        }
+    }
+
+    fn role_mut(&mut self, role: Role) -> &mut RoleInput {
+        match role {
+            Role::Driver => &mut self.driver,
+            Role::Operator => &mut self.operator,
+        }
+    }
+
+    /// Models of the controllers holding a role.
+    pub(crate) fn models(&self) -> Vec<ControllerModel> {
+        let mut models: Vec<ControllerModel> = Vec::new();
+        for model in Role::ALL
+            .into_iter()
+            .filter_map(|role| self.role(role).bindings.model)
+        {
+            if !models.contains(&model) {
+                models.push(model);
             }
-            EventType::AxisChanged(axis, value, ..) => {
-                self.update_axis(axis, value);
+        }
+        models
+    }
+
+    /// Forgets the input of a role whose controller was unassigned, so nothing stays held.
+    pub(crate) fn release(&mut self, role: Role) {
+        self.recorder.record(RecordedStep::Released(role));
+        *self.role_mut(role) = RoleInput::default();
+    }
+
+    /// Failsafe for a role whose controller disconnected.
+    pub(crate) fn disconnected(&mut self, role: Role) {
+        self.recorder.record(RecordedStep::Lost(role));
+        *self.role_mut(role) = RoleInput::default();
+        self.lost.insert(role);
+    }
+
+    pub(crate) fn reacquire(&mut self, role: Role) {
+        self.recorder.record(RecordedStep::Reacquired(role));
+        self.lost.remove(&role);
+    }
+
+    pub(crate) fn reset_estop(&mut self) {
+        self.recorder.record(RecordedStep::EStopReset);
+        self.estop = false;
+    }
+
+    pub(crate) fn rearm(&mut self) {
+        self.recorder.record(RecordedStep::Armed);
+        self.disarmed = false;
+    }
+
+    /// Whether outputs must be zero.
+    fn halted(&self) -> bool {
+        self.estop || self.disarmed || !self.lost.is_empty()
+    }
+
+    /// Feeds an event of the controller holding `roles`, one controller may hold both.
+    pub(crate) fn update(
+        &mut self,
+        roles: &[Role],
+        model: Option<ControllerModel>,
+        event: &InputEvent,
+    ) {
+        if self.recorder.replaying() {
+            self.watch_live(roles, model, Some(event), event.time);
+            return;
+        }
+        self.arrivals.push(event.time);
+        self.recorder.record(RecordedStep::Event {
+            roles: roles.to_vec(),
+            model,
+            event: *event,
+        });
+        self.feed(roles, model, event);
+    }
+
+    /// Feeds an event of the recording being replayed.
+    pub(crate) fn replayed(
+        &mut self,
+        roles: &[Role],
+        model: Option<ControllerModel>,
+        event: &InputEvent,
+    ) {
+        // events polled with the one that e-stopped the replay are dropped.
+        if self.recorder.replaying() {
+            self.feed(roles, model, event);
+        }
+    }
+
+    fn feed(&mut self, roles: &[Role], model: Option<ControllerModel>, event: &InputEvent) {
+        // while rebinding, events only pick the new input and don't reach the robot.
+        let capturing: bool = self.bindings.capturing();
+        let mut pressed: BTreeSet<Action> = BTreeSet::new();
+        for (index, role) in roles.iter().enumerate() {
+            let bindings: &mut Bindings = &mut self.bindings;
+            let role_input: &mut RoleInput = match role {
+                Role::Driver => &mut self.driver,
+                Role::Operator => &mut self.operator,
+            };
+            if capturing && index == 0 {
+                bindings.capture(model, event, &role_input.input);
             }
-            _ => (),
+            role_input.bindings.model = model;
+            role_input.input.update(event);
+            let edges: Vec<Action> =
+                bindings.update(&role_input.input, &mut role_input.bindings, event.time);
+            pressed.extend(
+                edges
+                    .into_iter()
+                    .filter(|action| action.role().is_none_or(|r| r == *role)),
+            );
+        }
+        if capturing {
+            // capturing must never block the e-stop.
+            pressed.retain(|action| *action == Action::EStop);
+            self.handle(pressed);
+            return;
+        }
+
+        self.handle(pressed);
+    }
+
+    /// Feeds live input to the e-stop binding while a recording replays, an e-stop aborts the replay.
+    fn watch_live(
+        &mut self,
+        roles: &[Role],
+        model: Option<ControllerModel>,
+        event: Option<&InputEvent>,
+        now: SystemTime,
+    ) {
+        let mut estop: bool = false;
+        for role in roles {
+            let role_input: &mut RoleInput = self.live.entry(*role).or_default();
+            if let Some(event) = event {
+                role_input.bindings.model = model;
+                role_input.input.update(event);
+            }
+            estop |= self
+                .bindings
+                .update(&role_input.input, &mut role_input.bindings, now)
+                .contains(&Action::EStop);
+        }
+        if estop {
+            self.recorder.abort();
+            self.estop = true;
+            self.end_replay();
+        }
+    }
+
+    /// Trades the settings with `other`, the arm keeps its positions.
+    fn swap_settings(&mut self, other: &mut JointState) {
+        std::mem::swap(&mut self.filters, &mut other.filters);
+        std::mem::swap(&mut self.bindings, &mut other.bindings);
+        std::mem::swap(&mut self.schemes, &mut other.schemes);
+        std::mem::swap(&mut self.speed_modes, &mut other.speed_modes);
+        let arm: ArmState = self.arm.clone();
+        self.arm.merge(&arm, other.arm.clone());
+        other.arm = arm;
+    }
+
+    /// Lets go of whatever the replay held and brings the live settings back.
+    fn end_replay(&mut self) {
+        self.recorder.stop();
+        // the replayed input must not stay held once live input takes over.
+        for role in Role::ALL {
+            *self.role_mut(role) = RoleInput::default();
+        }
+        self.live.clear();
+        if let Some(mut settings) = self.before_replay.take() {
+            self.swap_settings(&mut settings);
+        }
+    }
+
+    /// Re-evaluates the bindings without a new event, so held gestures fire on time.
+    pub(crate) fn tick(&mut self, now: SystemTime) {
+        if self.recorder.replaying() {
+            // held e-stop gestures need a tick as well.
+            let roles: Vec<Role> = self.live.keys().copied().collect();
+            self.watch_live(&roles, None, None, now);
+            let finished: bool = self
+                .recorder
+                .source()
+                .is_some_and(|source| source.finished());
+            if finished {
+                self.end_replay();
+            }
+        }
+        self.recorder.record(RecordedStep::Tick {
+            time: now,
+            output: (0f64, 0f64),
+        });
+
+        let mut pressed: BTreeSet<Action> = BTreeSet::new();
+        for role in Role::ALL {
+            let role_input: &mut RoleInput = match role {
+                Role::Driver => &mut self.driver,
+                Role::Operator => &mut self.operator,
+            };
+            role_input.filtered = role_input.input.clone();
+            self.filters
+                .apply(role, &mut role_input.filters, &mut role_input.filtered, now);
+            let edges: Vec<Action> =
+                self.bindings
+                    .update(&role_input.input, &mut role_input.bindings, now);
+            pressed.extend(
+                edges
+                    .into_iter()
+                    .filter(|action| action.role().is_none_or(|r| r == role)),
+            );
+        }
+        if self.bindings.capturing() {
+            pressed.retain(|action| *action == Action::EStop);
+        }
+        self.handle(pressed);
+    }
+
+    fn handle(&mut self, pressed: BTreeSet<Action>) {
+        for action in &pressed {
+            match action {
+                Action::ResetEStop => self.estop = false,
+                Action::CycleScheme => self.schemes.cycle(),
+                Action::Arm => self.disarmed = false,
+                Action::ResetOdometry => self.reset_odometry = true,
+                Action::SpeedUp => self.speed_modes.gear_up(),
+                Action::SpeedDown => self.speed_modes.gear_down(),
+                Action::PoseSlot(slot) if !self.estop => self.arm.press_slot(*slot),
+                _ => (),
+            }
+        }
+        // checked last so an e-stop always wins over a reset in the same batch.
+        if pressed.contains(&Action::EStop) {
+            self.estop = true;
+        }
+        if pressed.contains(&Action::Disarm) {
+            self.disarmed = true;
+        }
+        self.speed_modes
+            .hold_turbo_modifier(self.action(Action::TurboModifier) >= 0.5);
+    }
+
+    /// Value read from the controller of the action's role, global actions from either.
+    fn action(&self, action: Action) -> f64 {
+        let value = |role: Role| -> f32 {
+            let role_input: &RoleInput = self.role(role);
+            self.bindings
+                .value(action, &role_input.filtered, &role_input.bindings)
+        };
+        match action.role() {
+            Some(role) => value(role) as f64,
+            None => value(Role::Driver).max(value(Role::Operator)) as f64,
+        }
+    }
+
+    /// Advances the arm, in cartesian mode the sticks and triggers jog the end effector.
+    pub(crate) fn step_arm(&mut self, profile: &RobotProfile, dt: f64) {
+        if self.halted() {
+            self.arm.halt();
+            return;
+        }
+
+        let inputs: Vec<JointInput> = (0..ARM_JOINT_ACTIONS)
+            .map(Action::ArmJoint)
+            .map(|action| JointInput {
+                value: self.action(action),
+                absolute: self.bindings.is_absolute(action, &self.operator.bindings),
+            })
+            .collect();
+        let cartesian: [f64; 6] = [
+            Action::CartesianX,
+            Action::CartesianY,
+            Action::CartesianZ,
+            Action::CartesianRoll,
+            Action::CartesianPitch,
+            Action::CartesianYaw,
+        ]
+        .map(|action| self.action(action));
+        self.arm.step(profile, &inputs, cartesian, dt);
+    }
+
+    /// Mixed left and right outputs scaled by the active speed mode.
+    pub(crate) fn drive_output(&mut self) -> (f64, f64) {
+        let output: (f64, f64) = if self.halted() {
+            (0f64, 0f64)
+        } else {
+            let mixed: (f64, f64) = match self.schemes.selected {
+                DriveScheme::Arcade => self.axis_to_differential_drive(),
+                DriveScheme::Trigger => self.triggers_to_differential_drive(),
+                DriveScheme::Tank => self.tank_to_differential_drive(),
+            };
+            self.speed_modes.apply(mixed)
+        };
+        self.recorder.output(output);
+        output
+    }
+
+    #[cfg(feature = "serde")]
+    fn play(&mut self, step: RecordedStep) {
+        match step {
+            RecordedStep::Event {
+                roles,
+                model,
+                event,
+            } => self.feed(&roles, model, &event),
+            RecordedStep::Tick { time, .. } => self.tick(time),
+            RecordedStep::Released(role) => self.release(role),
+            RecordedStep::Lost(role) => self.disconnected(role),
+            RecordedStep::Reacquired(role) => self.reacquire(role),
+            RecordedStep::EStopReset => self.reset_estop(),
+            RecordedStep::Armed => self.rearm(),
         }
     }
 
-    fn update_button(&mut self, button: Button, value: f32) {
-        match button {
-            Button::Start => self.start = value,
-            Button::Select => self.select = value,
-            Button::LeftTrigger => self.left_trigger_1 = value,
-            Button::LeftTrigger2 => self.left_trigger_2 = value,
-            Button::RightTrigger => self.right_trigger_1 = value,
-            Button::RightTrigger2 => self.right_trigger_2 = value,
-            _ => (),
+    /// Replays a recording on the settings it was made with, the outputs must match the recorded ones.
+    #[cfg(feature = "serde")]
+    pub(crate) fn verify(recording: &Recording) -> Result<String, String> {
+        let mut joints: JointState = ron::from_str(&recording.settings)
+            .map_err(|error| format!("Invalid settings: {error}"))?;
+        joints.estop = recording.estop;
+        joints.disarmed = recording.disarmed;
+        joints.lost = recording.lost.clone();
+        let expected: Vec<(f64, f64)> = recording.outputs();
+        let mut tick: usize = 0;
+        for step in recording.steps.iter().cloned() {
+            let ticked: bool = matches!(step, RecordedStep::Tick { .. });
+            joints.play(step);
+            if ticked {
+                let output: (f64, f64) = joints.drive_output();
+                let recorded: (f64, f64) = *expected
+                    .get(tick)
+                    .ok_or_else(|| format!("Output {tick} is missing from the recording"))?;
+                if output != recorded {
+                    return Err(format!(
+                        "Output {tick} differs, recorded {recorded:?} replayed {output:?}"
+                    ));
+                }
+                tick += 1;
+            }
         }
+        Ok(format!("All {tick} outputs are identical"))
     }
 
-    fn update_axis(&mut self, axis: Axis, value: f32) {
-        match axis {
-            Axis::LeftStickX => self.left_axis_x = value,
-            Axis::LeftStickY => self.left_axis_y = value,
-            Axis::RightStickX => self.right_axis_x = value,
-            Axis::RightStickY => self.right_axis_y = value,
-            _ => (),
+    /// Carries out a request of the recording UI, saving and verifying are left to the UI.
+    pub(crate) fn request(&mut self, request: RecorderRequest) {
+        match request {
+            RecorderRequest::Record => {
+                // replays start without anything held, like the recording.
+                for role in Role::ALL {
+                    *self.role_mut(role) = RoleInput::default();
+                }
+                #[cfg(feature = "serde")]
+                let settings: String = ron::to_string(self).unwrap_or_default();
+                #[cfg(not(feature = "serde"))]
+                let settings: String = String::new();
+                self.recorder.start(
+                    settings,
+                    self.estop,
+                    self.disarmed,
+                    self.lost.clone(),
+                    time_now(),
+                );
+            }
+            RecorderRequest::Replay => {
+                if let Some(recording) = self.recorder.last.clone() {
+                    // the safety state stays live, a replay can't clear an e-stop or a lost controller.
+                    #[cfg(feature = "serde")]
+                    match ron::from_str::<JointState>(&recording.settings) {
+                        Ok(mut settings) => {
+                            self.swap_settings(&mut settings);
+                            self.before_replay = Some(Box::new(settings));
+                        }
+                        Err(error) => {
+                            self.recorder.report = Some(Err(format!("Invalid settings: {error}")));
+                            return;
+                        }
+                    }
+                    for role in Role::ALL {
+                        *self.role_mut(role) = RoleInput::default();
+                    }
+                    self.live.clear();
+                    self.recorder.replay(recording);
+                }
+            }
+            RecorderRequest::Stop => {
+                if self.recorder.replaying() {
+                    self.end_replay();
+                } else {
+                    self.recorder.stop();
+                }
+            }
+            // both run on a copy of the recording, the loop mustn't wait for them.
+            #[cfg(feature = "serde")]
+            RecorderRequest::Save | RecorderRequest::Verify => (),
         }
     }
 
+    pub(crate) fn triggers_to_differential_drive(&self) -> (f64, f64) {
+        // the left trigger brakes while the right one is held and reverses once it is released.
+        let throttle: f64 = self.action(Action::Throttle) - self.action(Action::Brake);
+        let steering: f64 = self.action(Action::Turn);
+
+        let turn_damping: f64 = 3f64;
+
+        let left: f64 = throttle + steering / turn_damping;
+        let right: f64 = throttle - steering / turn_damping;
+
+        // keep the ratio between both sides when one of them saturates.
+        let saturation: f64 = left.abs().max(right.abs()).max(1f64);
+
+        (left / saturation, right / saturation)
+    }
+
+    pub(crate) fn tank_to_differential_drive(&self) -> (f64, f64) {
+        let left: f64 = self.action(Action::TankLeft);
+        let right: f64 = self.action(Action::TankRight);
+
+        self.schemes.tank_coupling.apply((left, right))
+    }
+
     pub(crate) fn axis_to_differential_drive(&mut self) -> (f64, f64) {
-        let x: f64 = self.left_axis_x as f64;
-        let y: f64 = self.left_axis_y as f64;
+        let x: f64 = self.action(Action::Turn);
+        let y: f64 = self.action(Action::Drive);
 
         if x == 0f64 && y == 0f64 {
             (0f64, 0f64)
         } else {
             // convert to polar coordinates.
-            let theta: f64 = atan2(y, x);
-            let radius: f64 = sqrt(x * x + y * y);
+            let theta: f64 = y.atan2(x);
+            let radius: f64 = (x * x + y * y).sqrt();
 
             // this is the maximum radius for a given angle.
-            let maximum_radius: f64 = if abs(x) > abs(y) {
-                abs(radius / (x))
+            let maximum_radius: f64 = if x.abs() > y.abs() {
+                (radius / x).abs()
             } else {
-                abs(radius / (y))
+                (radius / y).abs()
             };
 
             // this is the actual throttle.
@@ -76,11 +517,278 @@ impl JointState {
 
             let turn_damping: f64 = 3f64;
 
-            let left: f64 = magnitude * (sin(theta) + cos(theta) / turn_damping);
-            let right: f64 = magnitude * (sin(theta) - cos(theta) / turn_damping);
+            let left: f64 = magnitude * (theta.sin() + theta.cos() / turn_damping);
+            let right: f64 = magnitude * (theta.sin() - theta.cos() / turn_damping);
 
             (left, right)
         }
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use gilrs::{Axis, Button};
+
+    use super::*;
+    use crate::command::recording::Recording;
+    use crate::command::speed_mode::SpeedMode;
+    use crate::input::event::InputEventKind;
+
+    fn button(joints: &mut JointState, role: Role, button: Button, value: f32) {
+        let code: u32 = button as u32;
+        let time: SystemTime = SystemTime::UNIX_EPOCH;
+        let press: InputEventKind = if value >= 0.5 {
+            InputEventKind::ButtonPressed { button, code }
+        } else {
+            InputEventKind::ButtonReleased { button, code }
+        };
+        for kind in [
+            press,
+            InputEventKind::ButtonChanged {
+                button,
+                code,
+                value,
+            },
+        ] {
+            joints.update(&[role], None, &InputEvent { time, kind });
+        }
+        // filters run on the control loop tick.
+        joints.tick(time);
+    }
+
+    fn stick(joints: &mut JointState, role: Role, axis: Axis, value: f32) {
+        let time: SystemTime = SystemTime::UNIX_EPOCH;
+        let kind: InputEventKind = InputEventKind::AxisChanged {
+            axis,
+            code: axis as u32,
+            value,
+        };
+        joints.update(&[role], None, &InputEvent { time, kind });
+        joints.tick(time);
+    }
+
+    fn triggers(throttle: f32, brake: f32, steering: f32) -> (f64, f64) {
+        let mut joints: JointState = JointState::default();
+        button(&mut joints, Role::Driver, Button::RightTrigger2, throttle);
+        button(&mut joints, Role::Driver, Button::LeftTrigger2, brake);
+        stick(&mut joints, Role::Driver, Axis::LeftStickX, steering);
+        joints.triggers_to_differential_drive()
+    }
+
+    #[test]
+    fn trigger_throttle_brake_and_reverse() {
+        assert_eq!(triggers(1.0, 0.0, 0.0), (1.0, 1.0));
+        assert_eq!(triggers(1.0, 1.0, 0.0), (0.0, 0.0));
+        assert_eq!(triggers(0.0, 0.5, 0.0), (-0.5, -0.5));
+    }
+
+    #[test]
+    fn trigger_steering_keeps_the_ratio_when_saturated() {
+        let (left, right): (f64, f64) = triggers(1.0, 0.0, 1.0);
+        assert_eq!(left, 1.0);
+        assert!((right - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn gears_shift_once_per_press() {
+        let mut joints: JointState = JointState::default();
+        button(&mut joints, Role::Driver, Button::DPadDown, 1.0);
+        button(&mut joints, Role::Driver, Button::DPadDown, 0.0);
+        button(&mut joints, Role::Driver, Button::DPadUp, 1.0);
+        // still held, not a new press.
+        button(&mut joints, Role::Driver, Button::DPadUp, 1.0);
+
+        assert!(joints.speed_modes.active() == SpeedMode::Normal);
+    }
+
+    #[test]
+    fn estop_zeroes_the_drive_until_reset() {
+        let mut joints: JointState = JointState::default();
+        stick(&mut joints, Role::Driver, Axis::LeftStickY, 1.0);
+        assert!(joints.drive_output().0 > 0f64);
+
+        button(&mut joints, Role::Driver, Button::Start, 1.0);
+        button(&mut joints, Role::Driver, Button::Start, 0.0);
+        assert!(joints.estop);
+        assert_eq!(joints.drive_output(), (0f64, 0f64));
+
+        joints.estop = false;
+        assert!(joints.drive_output().0 > 0f64);
+    }
+
+    #[test]
+    fn long_press_resets_estop_on_tick() {
+        let mut joints: JointState = JointState::default();
+        button(&mut joints, Role::Driver, Button::Start, 1.0);
+        button(&mut joints, Role::Driver, Button::RightThumb, 1.0);
+
+        joints.tick(SystemTime::UNIX_EPOCH + Duration::from_secs(1));
+        assert!(joints.estop);
+        joints.tick(SystemTime::UNIX_EPOCH + Duration::from_secs(2));
+        assert!(!joints.estop);
+    }
+
+    #[test]
+    fn disarm_needs_a_double_tap_and_arm_a_long_press() {
+        let mut joints: JointState = JointState::default();
+        stick(&mut joints, Role::Driver, Axis::LeftStickY, 1.0);
+        button(&mut joints, Role::Operator, Button::LeftThumb, 1.0);
+        button(&mut joints, Role::Operator, Button::LeftThumb, 0.0);
+        assert!(joints.drive_output().0 > 0f64);
+
+        button(&mut joints, Role::Operator, Button::LeftThumb, 1.0);
+        assert!(joints.disarmed);
+        assert_eq!(joints.drive_output(), (0f64, 0f64));
+
+        button(&mut joints, Role::Driver, Button::Mode, 1.0);
+        assert!(joints.disarmed);
+        joints.tick(SystemTime::UNIX_EPOCH + Duration::from_secs(2));
+        assert!(!joints.disarmed);
+        assert!(joints.drive_output().0 > 0f64);
+    }
+
+    #[test]
+    fn odometry_resets_after_a_long_press() {
+        let mut joints: JointState = JointState::default();
+        button(&mut joints, Role::Driver, Button::North, 1.0);
+        assert!(!joints.reset_odometry);
+        joints.tick(SystemTime::UNIX_EPOCH + Duration::from_secs(2));
+        assert!(joints.reset_odometry);
+    }
+
+    #[test]
+    fn pose_slots_recall_from_the_operator_only() {
+        let profile: RobotProfile = RobotProfile::default();
+        let mut joints: JointState = JointState::default();
+        button(&mut joints, Role::Driver, Button::DPadLeft, 1.0);
+        joints.step_arm(&profile, 0.1);
+        assert!(!joints.arm.moving());
+
+        button(&mut joints, Role::Operator, Button::DPadLeft, 1.0);
+        joints.step_arm(&profile, 0.1);
+        assert!(joints.arm.moving());
+    }
+
+    #[test]
+    fn drive_ignores_the_operator() {
+        let mut joints: JointState = JointState::default();
+        stick(&mut joints, Role::Operator, Axis::LeftStickY, 1.0);
+        assert_eq!(joints.drive_output(), (0f64, 0f64));
+
+        stick(&mut joints, Role::Driver, Axis::LeftStickY, 1.0);
+        assert!(joints.drive_output().0 > 0f64);
+    }
+
+    #[test]
+    fn global_actions_come_from_either_role() {
+        let mut joints: JointState = JointState::default();
+        button(&mut joints, Role::Operator, Button::Start, 1.0);
+
+        assert!(joints.estop);
+    }
+
+    #[test]
+    fn released_roles_forget_held_input() {
+        let mut joints: JointState = JointState::default();
+        stick(&mut joints, Role::Driver, Axis::LeftStickY, 1.0);
+        joints.release(Role::Driver);
+        joints.tick(SystemTime::UNIX_EPOCH);
+
+        assert_eq!(joints.drive_output(), (0f64, 0f64));
+    }
+
+    #[test]
+    fn lost_controller_zeroes_the_drive() {
+        let mut joints: JointState = JointState::default();
+        stick(&mut joints, Role::Driver, Axis::LeftStickY, 1.0);
+        assert!(joints.drive_output().0 > 0f64);
+
+        joints.disconnected(Role::Operator);
+        assert_eq!(joints.drive_output(), (0f64, 0f64));
+
+        joints.reacquire(Role::Operator);
+        assert!(joints.drive_output().0 > 0f64);
+    }
+
+    #[test]
+    fn estop_fires_while_capturing() {
+        let mut joints: JointState = JointState::default();
+        // an axis action waits for a second button, so the capture stays pending.
+        joints.bindings.start_capture(Action::Drive);
+        button(&mut joints, Role::Driver, Button::Start, 1.0);
+
+        assert!(joints.bindings.capturing());
+        assert!(joints.estop);
+    }
+
+    #[test]
+    fn estop_wins_over_reset() {
+        let mut joints: JointState = JointState::default();
+        joints.handle(BTreeSet::from([Action::EStop, Action::ResetEStop]));
+
+        assert!(joints.estop);
+    }
+
+    #[test]
+    fn live_estop_aborts_replay() {
+        let mut joints: JointState = JointState::default();
+        let start: SystemTime = SystemTime::UNIX_EPOCH;
+        let recording: Recording = Recording {
+            start,
+            settings: String::new(),
+            estop: false,
+            disarmed: false,
+            lost: BTreeSet::new(),
+            steps: vec![RecordedStep::Tick {
+                time: start + Duration::from_secs(60),
+                output: (0f64, 0f64),
+            }],
+        };
+        joints.recorder.replay(recording);
+        button(&mut joints, Role::Driver, Button::Start, 1.0);
+
+        assert!(joints.estop);
+        assert!(!joints.recorder.replaying());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn recording_verifies_after_a_round_trip() {
+        let mut joints: JointState = JointState::default();
+        let settings: String = ron::to_string(&joints).expect("serializable settings");
+        joints.recorder.start(
+            settings,
+            false,
+            false,
+            BTreeSet::new(),
+            SystemTime::UNIX_EPOCH,
+        );
+        for value in [0.2, 0.8, 1.0, 0.4, 0.0] {
+            stick(&mut joints, Role::Driver, Axis::LeftStickY, value);
+            joints.drive_output();
+        }
+        button(&mut joints, Role::Driver, Button::Start, 1.0);
+        joints.drive_output();
+        joints.recorder.stop();
+
+        let recording: Recording = joints.recorder.last.take().expect("recording");
+        let saved: String = ron::to_string(&recording).expect("serializable recording");
+        let mut recording: Recording = ron::from_str(&saved).expect("parsable recording");
+        assert_eq!(
+            JointState::verify(&recording),
+            Ok("All 6 outputs are identical".to_string())
+        );
+
+        if let Some(RecordedStep::Tick { output, .. }) = recording
+            .steps
+            .iter_mut()
+            .filter(|step| matches!(step, RecordedStep::Tick { .. }))
+            .nth(2)
+        {
+            output.0 += 0.1;
+        }
+        assert!(JointState::verify(&recording).is_err());
+    }
+}
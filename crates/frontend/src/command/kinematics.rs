@@ -0,0 +1,639 @@
+use std::f64::consts::{PI, TAU};
+use std::fmt::{Display, Formatter};
+
+use crate::robot::profile::{JointKind, JointProfile};
+
+pub(crate) type Vec3 = [f64; 3];
+pub(crate) type Mat3 = [[f64; 3]; 3];
+
+const IDENTITY: Mat3 = [[1f64, 0f64, 0f64], [0f64, 1f64, 0f64], [0f64, 0f64, 1f64]];
+
+// numeric solver tuning.
+const MAX_ITERATIONS: usize = 64;
+const POSITION_TOLERANCE: f64 = 1e-4;
+const ORIENTATION_TOLERANCE: f64 = 1e-3;
+const DAMPING: f64 = 0.05;
+// below this manipulability the chain is treated as being close to a singularity.
+pub(crate) const SINGULARITY_THRESHOLD: f64 = 1e-3;
+
+/// Position and orientation of a frame, relative to the arm base.
+#[derive(Clone, Copy)]
+pub(crate) struct Pose {
+    pub(crate) position: Vec3,
+    pub(crate) rotation: Mat3,
+}
+
+impl Default for Pose {
+    fn default() -> Self {
+        Self {
+            position: [0f64; 3],
+            rotation: IDENTITY,
+        }
+    }
+}
+
+impl Pose {
+    fn compose(&self, other: &Pose) -> Pose {
+        Pose {
+            position: add(self.position, mat_vec(&self.rotation, other.position)),
+            rotation: mat_mul(&self.rotation, &other.rotation),
+        }
+    }
+
+    /// Moves the pose by a linear and angular displacement expressed in the world or tool frame.
+    pub(crate) fn displaced(&self, linear: Vec3, angular: Vec3, frame: JogFrame) -> Pose {
+        match frame {
+            JogFrame::World => Pose {
+                position: add(self.position, linear),
+                rotation: mat_mul(&rotation_from_vector(angular), &self.rotation),
+            },
+            JogFrame::Tool => Pose {
+                position: add(self.position, mat_vec(&self.rotation, linear)),
+                rotation: mat_mul(&self.rotation, &rotation_from_vector(angular)),
+            },
+        }
+    }
+
+    /// Roll, pitch and yaw (ZYX convention) of the orientation, in radians.
+    pub(crate) fn roll_pitch_yaw(&self) -> Vec3 {
+        let r: &Mat3 = &self.rotation;
+        [
+            r[2][1].atan2(r[2][2]),
+            (-r[2][0]).clamp(-1f64, 1f64).asin(),
+            r[1][0].atan2(r[0][0]),
+        ]
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub(crate) enum JogFrame {
+    #[default]
+    World,
+    Tool,
+}
+
+impl Display for JogFrame {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JogFrame::World => write!(f, "🌍 World"),
+            JogFrame::Tool => write!(f, "🔧 Tool"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Solver {
+    // closed form for chains of two or three revolute joints with parallel axes.
+    AnalyticPlanar,
+    // damped least squares on the geometric jacobian.
+    DampedLeastSquares,
+}
+
+impl Display for Solver {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Solver::AnalyticPlanar => write!(f, "Analytic (planar)"),
+            Solver::DampedLeastSquares => write!(f, "Damped Least Squares"),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) enum IkError {
+    // the arm is empty.
+    NoJoints,
+    // the target is outside of the workspace.
+    Unreachable,
+    // a solution exists geometrically but needs a joint beyond its soft limits.
+    OutsideLimits(String),
+    // the numeric solver did not reach the tolerance.
+    NoConvergence,
+}
+
+impl Display for IkError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IkError::NoJoints => write!(f, "The robot profile has no joints"),
+            IkError::Unreachable => write!(f, "Target is out of reach"),
+            IkError::OutsideLimits(joint) => write!(f, "Target needs {joint} beyond its limits"),
+            IkError::NoConvergence => write!(f, "Solver did not converge"),
+        }
+    }
+}
+
+/// Serial chain described by the Denavit-Hartenberg parameters of the profile joints.
+pub(crate) struct Chain<'a> {
+    joints: &'a [JointProfile],
+}
+
+impl<'a> Chain<'a> {
+    pub(crate) fn new(joints: &'a [JointProfile]) -> Self {
+        Self { joints }
+    }
+
+    pub(crate) fn solver(&self) -> Solver {
+        if self.is_planar() {
+            Solver::AnalyticPlanar
+        } else {
+            Solver::DampedLeastSquares
+        }
+    }
+
+    /// All axes parallel to the base Z axis, so only X, Y and yaw can be controlled.
+    pub(crate) fn is_planar(&self) -> bool {
+        (2..=3).contains(&self.joints.len())
+            && self
+                .joints
+                .iter()
+                .all(|joint| joint.kind == JointKind::Revolute && joint.dh.alpha.abs() < 1e-9)
+    }
+
+    /// Frames of every joint, starting with the base and ending with the tool.
+    fn frames(&self, positions: &[f64]) -> Vec<Pose> {
+        let mut frames: Vec<Pose> = Vec::with_capacity(self.joints.len() + 1);
+        let mut current: Pose = Pose::default();
+        frames.push(current);
+        for (joint, q) in self.joints.iter().zip(positions) {
+            current = current.compose(&link_transform(joint, *q));
+            frames.push(current);
+        }
+        frames
+    }
+
+    pub(crate) fn forward(&self, positions: &[f64]) -> Pose {
+        self.frames(positions).last().copied().unwrap_or_default()
+    }
+
+    /// Geometric jacobian, one column of linear and angular velocity per joint.
+    fn jacobian(&self, positions: &[f64]) -> Vec<[f64; 6]> {
+        let frames: Vec<Pose> = self.frames(positions);
+        let tool: Vec3 = frames.last().map(|f| f.position).unwrap_or_default();
+
+        self.joints
+            .iter()
+            .zip(&frames)
+            .map(|(joint, frame)| {
+                let z: Vec3 = [
+                    frame.rotation[0][2],
+                    frame.rotation[1][2],
+                    frame.rotation[2][2],
+                ];
+                match joint.kind {
+                    JointKind::Revolute => {
+                        let linear: Vec3 = cross(z, sub(tool, frame.position));
+                        [linear[0], linear[1], linear[2], z[0], z[1], z[2]]
+                    }
+                    JointKind::Prismatic => [z[0], z[1], z[2], 0f64, 0f64, 0f64],
+                }
+            })
+            .collect()
+    }
+
+    /// Yoshikawa manipulability of the controllable task rows, zero at a singularity.
+    pub(crate) fn manipulability(&self, positions: &[f64]) -> f64 {
+        let jacobian: Vec<[f64; 6]> = self.jacobian(positions);
+        determinant(jacobian_product(&jacobian, self.task_rows(), 0f64))
+            .max(0f64)
+            .sqrt()
+    }
+
+    /// Rows of the pose error the chain can control, never more than it has joints.
+    fn task_rows(&self) -> &'static [usize] {
+        if self.is_planar() {
+            // a two link arm can't also set the yaw.
+            &[0, 1, 5][..self.joints.len()]
+        } else if self.joints.len() < 6 {
+            // orientation needs six joints outside the plane.
+            &[0, 1, 2][..self.joints.len().min(3)]
+        } else {
+            &[0, 1, 2, 3, 4, 5]
+        }
+    }
+
+    /// Joint positions that place the tool at `target`, preferring the one closest to `seed`.
+    pub(crate) fn inverse(&self, target: &Pose, seed: &[f64]) -> Result<Vec<f64>, IkError> {
+        if self.joints.is_empty() {
+            return Err(IkError::NoJoints);
+        }
+
+        match self.solver() {
+            Solver::AnalyticPlanar => self.inverse_planar(target, seed),
+            Solver::DampedLeastSquares => self.inverse_numeric(target, seed),
+        }
+    }
+
+    fn inverse_planar(&self, target: &Pose, seed: &[f64]) -> Result<Vec<f64>, IkError> {
+        let [x, y, _] = target.position;
+        let phi: f64 = target.rotation[1][0].atan2(target.rotation[0][0]);
+
+        // the third link only changes the orientation, so solve for the wrist center.
+        let (wrist_x, wrist_y) = match self.joints.get(2) {
+            Some(wrist) => (x - wrist.dh.a * phi.cos(), y - wrist.dh.a * phi.sin()),
+            None => (x, y),
+        };
+
+        let l1: f64 = self.joints[0].dh.a;
+        let l2: f64 = self.joints[1].dh.a;
+        let cos_q2: f64 =
+            (wrist_x * wrist_x + wrist_y * wrist_y - l1 * l1 - l2 * l2) / (2f64 * l1 * l2);
+        if !cos_q2.is_finite() || cos_q2.abs() > 1f64 + 1e-9 {
+            return Err(IkError::Unreachable);
+        }
+
+        let mut best: Option<Vec<f64>> = None;
+        let mut error: IkError = IkError::Unreachable;
+        // elbow up and elbow down.
+        for sign in [1f64, -1f64] {
+            let q2: f64 = sign * cos_q2.clamp(-1f64, 1f64).acos();
+            let q1: f64 = wrist_y.atan2(wrist_x) - (l2 * q2.sin()).atan2(l1 + l2 * q2.cos());
+            let mut angles: Vec<f64> = vec![q1, q2];
+            if self.joints.len() == 3 {
+                angles.push(phi - q1 - q2);
+            }
+
+            let candidate: Vec<f64> = angles
+                .iter()
+                .zip(self.joints)
+                .zip(seed.iter().chain(std::iter::repeat(&0f64)))
+                .map(|((angle, joint), seed)| {
+                    // pick the 2π equivalent closest to the seed.
+                    let raw: f64 = angle - joint.dh.theta_offset;
+                    seed + wrap_angle(raw - seed)
+                })
+                .collect();
+
+            if let Some((joint, _)) = self
+                .joints
+                .iter()
+                .zip(&candidate)
+                .find(|(joint, q)| **q < joint.min_position || **q > joint.max_position)
+            {
+                error = IkError::OutsideLimits(joint.name.clone());
+                continue;
+            }
+
+            let closer: bool = match &best {
+                Some(best) => distance(&candidate, seed) < distance(best, seed),
+                None => true,
+            };
+            if closer {
+                best = Some(candidate);
+            }
+        }
+
+        best.ok_or(error)
+    }
+
+    fn inverse_numeric(&self, target: &Pose, seed: &[f64]) -> Result<Vec<f64>, IkError> {
+        let rows: &[usize] = self.task_rows();
+        let mut positions: Vec<f64> = self
+            .joints
+            .iter()
+            .zip(seed.iter().chain(std::iter::repeat(&0f64)))
+            .map(|(joint, q)| joint.clamp(*q))
+            .collect();
+
+        for _ in 0..MAX_ITERATIONS {
+            let current: Pose = self.forward(&positions);
+            let error: [f64; 6] = pose_error(target, &current);
+            // rows outside the task can't be controlled, so they don't have to converge.
+            let task_error: Vec<f64> = rows.iter().map(|row| error[*row]).collect();
+            let (position, orientation) = task_error.split_at(rows.partition_point(|row| *row < 3));
+            if norm(position) < POSITION_TOLERANCE && norm(orientation) < ORIENTATION_TOLERANCE {
+                return Ok(positions);
+            }
+
+            let jacobian: Vec<[f64; 6]> = self.jacobian(&positions);
+
+            // near a singularity the damping trades accuracy for bounded joint steps.
+            let manipulability: f64 = determinant(jacobian_product(&jacobian, rows, 0f64))
+                .max(0f64)
+                .sqrt();
+            let damping: f64 = if manipulability < SINGULARITY_THRESHOLD {
+                DAMPING * (2f64 - manipulability / SINGULARITY_THRESHOLD)
+            } else {
+                DAMPING
+            };
+
+            let product: Vec<Vec<f64>> = jacobian_product(&jacobian, rows, damping * damping);
+            let Some(solution) = solve(product, task_error) else {
+                return Err(IkError::NoConvergence);
+            };
+
+            for ((q, column), joint) in positions.iter_mut().zip(&jacobian).zip(self.joints) {
+                let step: f64 = rows
+                    .iter()
+                    .zip(&solution)
+                    .map(|(row, value)| column[*row] * value)
+                    .sum();
+                *q = joint.clamp(*q + step);
+            }
+        }
+
+        // the chain is stuck against a limit or the target is outside the workspace.
+        let reach: f64 = self
+            .joints
+            .iter()
+            .map(|joint| {
+                let stroke: f64 = match joint.kind {
+                    JointKind::Revolute => 0f64,
+                    JointKind::Prismatic => joint.max_position.abs().max(joint.min_position.abs()),
+                };
+                joint.dh.a.abs() + joint.dh.d.abs() + stroke
+            })
+            .sum();
+        if norm(&target.position) > reach {
+            Err(IkError::Unreachable)
+        } else {
+            Err(IkError::NoConvergence)
+        }
+    }
+}
+
+fn link_transform(joint: &JointProfile, position: f64) -> Pose {
+    let (theta, d) = match joint.kind {
+        JointKind::Revolute => (position + joint.dh.theta_offset, joint.dh.d),
+        JointKind::Prismatic => (joint.dh.theta_offset, position + joint.dh.d),
+    };
+    let (st, ct) = theta.sin_cos();
+    let (sa, ca) = joint.dh.alpha.sin_cos();
+
+    Pose {
+        position: [joint.dh.a * ct, joint.dh.a * st, d],
+        rotation: [
+            [ct, -st * ca, st * sa],
+            [st, ct * ca, -ct * sa],
+            [0f64, sa, ca],
+        ],
+    }
+}
+
+/// Linear and angular error between two poses, the angular part as a rotation vector.
+fn pose_error(target: &Pose, current: &Pose) -> [f64; 6] {
+    let linear: Vec3 = sub(target.position, current.position);
+    let mut angular: Vec3 = [0f64; 3];
+    for column in 0..3 {
+        let c: Vec3 = [
+            current.rotation[0][column],
+            current.rotation[1][column],
+            current.rotation[2][column],
+        ];
+        let t: Vec3 = [
+            target.rotation[0][column],
+            target.rotation[1][column],
+            target.rotation[2][column],
+        ];
+        angular = add(angular, cross(c, t));
+    }
+    [
+        linear[0],
+        linear[1],
+        linear[2],
+        angular[0] / 2f64,
+        angular[1] / 2f64,
+        angular[2] / 2f64,
+    ]
+}
+
+/// `J Jᵀ + λ² I` restricted to the task rows.
+fn jacobian_product(jacobian: &[[f64; 6]], rows: &[usize], damping: f64) -> Vec<Vec<f64>> {
+    rows.iter()
+        .enumerate()
+        .map(|(i, row_i)| {
+            rows.iter()
+                .enumerate()
+                .map(|(j, row_j)| {
+                    let dot: f64 = jacobian
+                        .iter()
+                        .map(|column| column[*row_i] * column[*row_j])
+                        .sum();
+                    if i == j {
+                        dot + damping
+                    } else {
+                        dot
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Gaussian elimination with partial pivoting, `None` for a singular matrix.
+fn solve(mut matrix: Vec<Vec<f64>>, mut rhs: Vec<f64>) -> Option<Vec<f64>> {
+    let n: usize = rhs.len();
+    for column in 0..n {
+        let pivot: usize = (column..n).max_by(|a, b| {
+            matrix[*a][column]
+                .abs()
+                .total_cmp(&matrix[*b][column].abs())
+        })?;
+        if matrix[pivot][column].abs() < 1e-12 {
+            return None;
+        }
+        matrix.swap(column, pivot);
+        rhs.swap(column, pivot);
+
+        for row in column + 1..n {
+            let factor: f64 = eliminate(&mut matrix, column, row);
+            rhs[row] -= factor * rhs[column];
+        }
+    }
+
+    let mut solution: Vec<f64> = vec![0f64; n];
+    for row in (0..n).rev() {
+        let tail: f64 = (row + 1..n).map(|k| matrix[row][k] * solution[k]).sum();
+        solution[row] = (rhs[row] - tail) / matrix[row][row];
+    }
+    Some(solution)
+}
+
+/// Subtracts a multiple of the pivot row to zero the `column` entry of `row`, returns the multiple.
+fn eliminate(matrix: &mut [Vec<f64>], column: usize, row: usize) -> f64 {
+    let factor: f64 = matrix[row][column] / matrix[column][column];
+    let (pivots, rest) = matrix.split_at_mut(row);
+    for (value, pivot) in rest[0].iter_mut().zip(&pivots[column]).skip(column) {
+        *value -= factor * pivot;
+    }
+    factor
+}
+
+fn determinant(mut matrix: Vec<Vec<f64>>) -> f64 {
+    let n: usize = matrix.len();
+    let mut determinant: f64 = 1f64;
+    for column in 0..n {
+        let Some(pivot) = (column..n).max_by(|a, b| {
+            matrix[*a][column]
+                .abs()
+                .total_cmp(&matrix[*b][column].abs())
+        }) else {
+            return 0f64;
+        };
+        if matrix[pivot][column] == 0f64 {
+            return 0f64;
+        }
+        if pivot != column {
+            matrix.swap(column, pivot);
+            determinant = -determinant;
+        }
+        determinant *= matrix[column][column];
+        for row in column + 1..n {
+            eliminate(&mut matrix, column, row);
+        }
+    }
+    determinant
+}
+
+/// Rodrigues' formula for a rotation vector.
+fn rotation_from_vector(vector: Vec3) -> Mat3 {
+    let angle: f64 = norm(&vector);
+    if angle < 1e-12 {
+        return IDENTITY;
+    }
+    let [x, y, z] = [vector[0] / angle, vector[1] / angle, vector[2] / angle];
+    let (s, c) = angle.sin_cos();
+    let t: f64 = 1f64 - c;
+    [
+        [t * x * x + c, t * x * y - s * z, t * x * z + s * y],
+        [t * x * y + s * z, t * y * y + c, t * y * z - s * x],
+        [t * x * z - s * y, t * y * z + s * x, t * z * z + c],
+    ]
+}
+
+fn wrap_angle(angle: f64) -> f64 {
+    (angle + PI).rem_euclid(TAU) - PI
+}
+
+fn distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| (a - b) * (a - b))
+        .sum::<f64>()
+        .sqrt()
+}
+
+fn norm(vector: &[f64]) -> f64 {
+    vector.iter().map(|v| v * v).sum::<f64>().sqrt()
+}
+
+fn add(a: Vec3, b: Vec3) -> Vec3 {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn sub(a: Vec3, b: Vec3) -> Vec3 {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn cross(a: Vec3, b: Vec3) -> Vec3 {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn mat_vec(m: &Mat3, v: Vec3) -> Vec3 {
+    [
+        m[0][0] * v[0] + m[0][1] * v[1] + m[0][2] * v[2],
+        m[1][0] * v[0] + m[1][1] * v[1] + m[1][2] * v[2],
+        m[2][0] * v[0] + m[2][1] * v[1] + m[2][2] * v[2],
+    ]
+}
+
+fn mat_mul(a: &Mat3, b: &Mat3) -> Mat3 {
+    let mut result: Mat3 = [[0f64; 3]; 3];
+    for (i, row) in result.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::robot::profile::DhParameters;
+
+    fn joint(a: f64, alpha: f64, d: f64) -> JointProfile {
+        JointProfile {
+            min_position: -PI,
+            max_position: PI,
+            dh: DhParameters {
+                a,
+                alpha,
+                d,
+                theta_offset: 0f64,
+            },
+            ..JointProfile::default()
+        }
+    }
+
+    fn position_error(chain: &Chain, target: &Pose, positions: &[f64]) -> f64 {
+        norm(&pose_error(target, &chain.forward(positions))[..3])
+    }
+
+    #[test]
+    fn planar_closed_form_round_trip() {
+        let joints: Vec<JointProfile> = vec![
+            joint(0.3, 0.0, 0.0),
+            joint(0.2, 0.0, 0.0),
+            joint(0.1, 0.0, 0.0),
+        ];
+        let chain: Chain = Chain::new(&joints);
+        assert!(chain.solver() == Solver::AnalyticPlanar);
+
+        let positions: [f64; 3] = [0.3, 0.5, -0.4];
+        let target: Pose = chain.forward(&positions);
+        let solution: Vec<f64> = chain
+            .inverse(&target, &[0.2, 0.4, -0.3])
+            .unwrap_or_else(|e| panic!("{e}"));
+
+        assert!(distance(&solution, &positions) < 1e-9);
+        assert!(norm(&pose_error(&target, &chain.forward(&solution))) < 1e-9);
+    }
+
+    #[test]
+    fn damped_least_squares_converges() {
+        let joints: Vec<JointProfile> = vec![
+            joint(0.0, PI / 2f64, 0.1),
+            joint(0.3, 0.0, 0.0),
+            joint(0.2, 0.0, 0.0),
+        ];
+        let chain: Chain = Chain::new(&joints);
+        assert!(chain.solver() == Solver::DampedLeastSquares);
+
+        let target: Pose = chain.forward(&[0.2, 0.3, 0.1]);
+        let solution: Vec<f64> = chain
+            .inverse(&target, &[0.1, 0.2, 0.0])
+            .unwrap_or_else(|e| panic!("{e}"));
+
+        assert!(position_error(&chain, &target, &solution) < POSITION_TOLERANCE);
+    }
+
+    #[test]
+    fn two_link_arm_is_singular_only_when_stretched() {
+        let joints: Vec<JointProfile> = vec![joint(0.3, 0.0, 0.0), joint(0.2, 0.0, 0.0)];
+        let chain: Chain = Chain::new(&joints);
+
+        assert!(chain.manipulability(&[0.3, 0.5]) > SINGULARITY_THRESHOLD);
+        assert!(chain.manipulability(&[0.3, -1.2]) > SINGULARITY_THRESHOLD);
+        assert!(chain.manipulability(&[0.3, 0.0]) < SINGULARITY_THRESHOLD);
+    }
+
+    #[test]
+    fn flipped_axes_are_not_planar() {
+        let joints: Vec<JointProfile> = vec![joint(0.3, 0.0, 0.0), joint(0.2, PI, 0.0)];
+        assert!(!Chain::new(&joints).is_planar());
+    }
+
+    #[test]
+    fn out_of_reach_targets_are_rejected() {
+        let joints: Vec<JointProfile> = vec![joint(0.3, 0.0, 0.0), joint(0.2, 0.0, 0.0)];
+        let target: Pose = Pose {
+            position: [1.0, 0.0, 0.0],
+            rotation: IDENTITY,
+        };
+        assert!(Chain::new(&joints).inverse(&target, &[0.0, 0.0]) == Err(IkError::Unreachable));
+    }
+}
@@ -0,0 +1,333 @@
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+use std::time::SystemTime;
+
+use egui::DragValue;
+use egui_plot::{Bar, BarChart, Legend, VLine};
+use gilrs_core::utils::time_now;
+
+use crate::command::control_loop::Shared;
+
+/// Point of the control loop an input has made it to.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Stage {
+    // polled by the control loop and fed to the bindings.
+    Received,
+    Filtered,
+    // mixed into drive outputs.
+    Mixed,
+    // drive command handed off for sending.
+    Commanded,
+}
+
+impl Stage {
+    pub(crate) const ALL: [Stage; 4] = [
+        Stage::Received,
+        Stage::Filtered,
+        Stage::Mixed,
+        Stage::Commanded,
+    ];
+}
+
+impl Display for Stage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Stage::Received => write!(f, "Received"),
+            Stage::Filtered => write!(f, "Filtered"),
+            Stage::Mixed => write!(f, "Mixed"),
+            Stage::Commanded => write!(f, "Commanded"),
+        }
+    }
+}
+
+/// Percentiles of one stage, milliseconds.
+struct Summary {
+    samples: usize,
+    p50: f64,
+    p90: f64,
+    p99: f64,
+    max: f64,
+    // fraction of the samples over the budget.
+    over_budget: f64,
+}
+
+/// Time from each gamepad event to every stage of the control loop.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub(crate) struct Latency {
+    // ms from the event to the command, the target the teleop has to meet.
+    budget: f64,
+    // samples kept per stage.
+    capacity: usize,
+    // milliseconds per stage, in the order of `Stage::ALL`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    samples: [VecDeque<f64>; 4],
+    #[cfg_attr(feature = "serde", serde(skip))]
+    export_error: Option<String>,
+}
+
+impl Default for Latency {
+    fn default() -> Self {
+        Self {
+            budget: 50.0,
+            capacity: 10_000,
+            samples: Default::default(),
+            export_error: None,
+        }
+    }
+}
+
+impl Latency {
+    fn index(stage: Stage) -> usize {
+        Stage::ALL
+            .iter()
+            .position(|s| *s == stage)
+            .unwrap_or_default()
+    }
+
+    /// Records how long the events took to reach `stage`, at `now`.
+    pub(crate) fn record(&mut self, events: &[SystemTime], stage: Stage, now: SystemTime) {
+        let samples: &mut VecDeque<f64> = &mut self.samples[Self::index(stage)];
+        // events stamped after `now` come from a clock that can't be compared.
+        for elapsed in events
+            .iter()
+            .filter_map(|time| now.duration_since(*time).ok())
+        {
+            samples.push_back(elapsed.as_secs_f64() * 1000f64);
+        }
+        while samples.len() > self.capacity.max(1) {
+            samples.pop_front();
+        }
+    }
+
+    fn summary(&self, stage: Stage) -> Option<Summary> {
+        let mut sorted: Vec<f64> = self.samples[Self::index(stage)].iter().copied().collect();
+        if sorted.is_empty() {
+            return None;
+        }
+        sorted.sort_by(f64::total_cmp);
+        let percentile = |p: f64| -> f64 {
+            let rank: usize = ((p / 100f64) * (sorted.len() - 1) as f64).round() as usize;
+            sorted[rank]
+        };
+        let over: usize = sorted.iter().filter(|ms| **ms > self.budget).count();
+        Some(Summary {
+            samples: sorted.len(),
+            p50: percentile(50f64),
+            p90: percentile(90f64),
+            p99: percentile(99f64),
+            max: sorted[sorted.len() - 1],
+            over_budget: over as f64 / sorted.len() as f64,
+        })
+    }
+
+    fn summary_csv(&self) -> String {
+        let mut csv: String = format!(
+            "stage,samples,p50_ms,p90_ms,p99_ms,max_ms,over_{}ms_percent\n",
+            self.budget
+        );
+        for stage in Stage::ALL {
+            if let Some(summary) = self.summary(stage) {
+                csv += &format!(
+                    "{stage},{},{:.3},{:.3},{:.3},{:.3},{:.2}\n",
+                    summary.samples,
+                    summary.p50,
+                    summary.p90,
+                    summary.p99,
+                    summary.max,
+                    summary.over_budget * 100f64
+                );
+            }
+        }
+        csv
+    }
+
+    fn samples_csv(&self) -> String {
+        let mut csv: String = "stage,latency_ms\n".to_string();
+        for stage in Stage::ALL {
+            for ms in &self.samples[Self::index(stage)] {
+                csv += &format!("{stage},{ms:.3}\n");
+            }
+        }
+        csv
+    }
+
+    fn export(&mut self, name: &str, contents: String) {
+        let name: String = crate::file::stamped(name, time_now());
+        self.export_error = crate::file::save(&name, &contents).err();
+    }
+
+    pub(crate) fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.heading("⏱ Input Latency");
+        ui.label("Time from each gamepad event to every stage of the control loop. The robot link doesn't acknowledge commands yet, so measurements end at the hand-off of the drive command.");
+
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Budget");
+            ui.add(
+                DragValue::new(&mut self.budget)
+                    .speed(1.0)
+                    .clamp_range(1.0..=1000.0)
+                    .suffix(" ms"),
+            );
+            ui.label("Keep");
+            ui.add(
+                DragValue::new(&mut self.capacity)
+                    .speed(100.0)
+                    .clamp_range(100..=1_000_000),
+            );
+
+            ui.separator();
+
+            if ui.button("🗑 Clear").clicked() {
+                self.samples = Default::default();
+            }
+            if ui.button("💾 Summary CSV").clicked() {
+                self.export("latency_summary.csv", self.summary_csv());
+            }
+            if ui.button("💾 Samples CSV").clicked() {
+                self.export("latency_samples.csv", self.samples_csv());
+            }
+            if let Some(error) = &self.export_error {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+        });
+
+        ui.separator();
+
+        egui::Grid::new("latency_grid")
+            .striped(true)
+            .num_columns(7)
+            .show(ui, |ui| {
+                for header in [
+                    "Stage",
+                    "Samples",
+                    "p50",
+                    "p90",
+                    "p99",
+                    "Max",
+                    "Over Budget",
+                ] {
+                    ui.strong(header);
+                }
+                ui.end_row();
+
+                for stage in Stage::ALL {
+                    ui.label(stage.to_string());
+                    match self.summary(stage) {
+                        Some(summary) => {
+                            ui.monospace(summary.samples.to_string());
+                            for ms in [summary.p50, summary.p90, summary.p99, summary.max] {
+                                let text: String = format!("{ms:.2} ms");
+                                if ms > self.budget {
+                                    ui.colored_label(ui.visuals().warn_fg_color, text);
+                                } else {
+                                    ui.monospace(text);
+                                }
+                            }
+                            ui.monospace(format!("{:.1} %", summary.over_budget * 100f64));
+                        }
+                        None => {
+                            ui.label("-");
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+
+        ui.separator();
+
+        // 1 ms bins up to the slowest sample, or the budget if everything is faster.
+        let slowest: f64 = self
+            .samples
+            .iter()
+            .flatten()
+            .copied()
+            .fold(self.budget, f64::max);
+        let bins: usize = (slowest.ceil() as usize + 1).min(1000);
+        let charts: Vec<BarChart> = Stage::ALL
+            .into_iter()
+            .map(|stage| {
+                let mut counts: Vec<usize> = vec![0; bins];
+                for ms in &self.samples[Self::index(stage)] {
+                    counts[(*ms as usize).min(bins - 1)] += 1;
+                }
+                let bars: Vec<Bar> = counts
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(_, count)| *count > 0)
+                    .map(|(bin, count)| Bar::new(bin as f64 + 0.5, count as f64).width(1.0))
+                    .collect();
+                BarChart::new(bars).name(stage.to_string())
+            })
+            .collect();
+        egui_plot::Plot::new("latency_histogram")
+            .height(ui.available_height().max(200f32))
+            .legend(Legend::default())
+            .x_axis_label("ms")
+            .y_axis_label("events")
+            .include_x(0.0)
+            .include_y(0.0)
+            .show(ui, |plot_ui| {
+                for chart in charts {
+                    plot_ui.bar_chart(chart);
+                }
+                plot_ui.vline(VLine::new(self.budget).name("Budget"));
+            });
+    }
+}
+
+impl Shared<Latency> {
+    /// Records from the control loop, see [`Latency::record`].
+    pub(crate) fn record(&mut self, events: &[SystemTime], stage: Stage, now: SystemTime) {
+        if events.is_empty() {
+            return;
+        }
+        let events: Vec<SystemTime> = events.to_vec();
+        self.update(move |latency| latency.record(&events, stage, now));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn at(millis: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn summary_percentiles() {
+        let mut latency: Latency = Latency::default();
+        // 0 to 100 ms, shuffled.
+        let events: Vec<SystemTime> = (0..=100).map(|ms| at(1000 - (ms * 37) % 101)).collect();
+        latency.record(&events, Stage::Commanded, at(1000));
+
+        assert!(latency.summary(Stage::Mixed).is_none());
+        let summary: Summary = latency.summary(Stage::Commanded).expect("samples");
+        assert_eq!(summary.samples, 101);
+        for (value, expected) in [
+            (summary.p50, 50.0),
+            (summary.p90, 90.0),
+            (summary.p99, 99.0),
+            (summary.max, 100.0),
+            (summary.over_budget, 50.0 / 101.0),
+        ] {
+            assert!((value - expected).abs() < 1e-9, "{value} != {expected}");
+        }
+    }
+
+    #[test]
+    fn record_skips_future_events_and_keeps_the_newest() {
+        let mut latency: Latency = Latency {
+            capacity: 2,
+            ..Latency::default()
+        };
+        latency.record(&[at(10), at(20), at(30), at(50)], Stage::Received, at(40));
+
+        let samples: Vec<f64> = latency.samples[0].iter().copied().collect();
+        assert_eq!(samples.len(), 2);
+        assert!((samples[0] - 20.0).abs() < 1e-9 && (samples[1] - 10.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,357 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::time::SystemTime;
+
+use egui::DragValue;
+use egui_plot::{Legend, Line, PlotPoints, VLine};
+use gilrs::{Axis, Button};
+
+use crate::command::control_loop::Shared;
+use crate::command::joints::JointState;
+use crate::input::role::Role;
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+enum Edge {
+    Rising,
+    Falling,
+    Either,
+}
+
+impl Edge {
+    const ALL: [Edge; 3] = [Edge::Rising, Edge::Falling, Edge::Either];
+
+    fn crossed(&self, previous: f64, value: f64, threshold: f64) -> bool {
+        let rising: bool = previous < threshold && value >= threshold;
+        let falling: bool = previous > threshold && value <= threshold;
+        match self {
+            Edge::Rising => rising,
+            Edge::Falling => falling,
+            Edge::Either => rising || falling,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+enum Group {
+    Axes,
+    Buttons,
+    Outputs,
+}
+
+// one trace, samples are seconds since the scope started and the value.
+type Trace = VecDeque<[f64; 2]>;
+
+/// Freezes the capture once a channel crosses a threshold.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+struct Trigger {
+    channel: Option<String>,
+    threshold: f64,
+    edge: Edge,
+    // waits for a crossing, otherwise the scope runs freely.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    armed: bool,
+    // time of the crossing, the capture goes on for half a window after it.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    fired: Option<f64>,
+}
+
+impl Default for Trigger {
+    fn default() -> Self {
+        Self {
+            channel: None,
+            threshold: 0.5,
+            edge: Edge::Either,
+            armed: false,
+            fired: None,
+        }
+    }
+}
+
+/// Scrolling plots of every input and the drive outputs, to spot drift and glitches.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub(crate) struct Oscilloscope {
+    // seconds shown.
+    window: f64,
+    trigger: Trigger,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    channels: BTreeMap<(Group, String), Trace>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    start: Option<SystemTime>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    frozen: bool,
+}
+
+impl Default for Oscilloscope {
+    fn default() -> Self {
+        Self {
+            window: 10.0,
+            trigger: Trigger::default(),
+            channels: BTreeMap::new(),
+            start: None,
+            frozen: false,
+        }
+    }
+}
+
+impl Oscilloscope {
+    /// Values of the inputs of both roles and the output of this step.
+    fn values(joints: &JointState, output: (f64, f64)) -> Vec<((Group, String), f64)> {
+        let mut values: Vec<((Group, String), f64)> = Vec::new();
+        for role in Role::ALL {
+            let name: &str = match role {
+                Role::Driver => "Driver",
+                Role::Operator => "Operator",
+            };
+            for (code, sample) in joints.role(role).input.axes() {
+                let axis: String = match sample.axis {
+                    Axis::Unknown => code.to_string(),
+                    axis => format!("{axis:?}"),
+                };
+                values.push(((Group::Axes, format!("{name} {axis}")), sample.value as f64));
+            }
+            for (code, sample) in joints.role(role).input.buttons() {
+                let button: String = match sample.button {
+                    Button::Unknown => code.to_string(),
+                    button => format!("{button:?}"),
+                };
+                let value: f32 = sample.value.max(f32::from(u8::from(sample.pressed)));
+                values.push(((Group::Buttons, format!("{name} {button}")), value as f64));
+            }
+        }
+        values.push(((Group::Outputs, "Left".to_string()), output.0));
+        values.push(((Group::Outputs, "Right".to_string()), output.1));
+        values
+    }
+
+    fn push(&mut self, now: SystemTime, values: Vec<((Group, String), f64)>) {
+        if self.frozen {
+            return;
+        }
+        let start: SystemTime = *self.start.get_or_insert(now);
+        let time: f64 = now.duration_since(start).unwrap_or_default().as_secs_f64();
+
+        for (key, value) in values {
+            let trace: &mut Trace = self.channels.entry(key.clone()).or_default();
+            let trigger: &mut Trigger = &mut self.trigger;
+            if trigger.armed && trigger.fired.is_none() && trigger.channel.as_ref() == Some(&key.1)
+            {
+                if let Some([_, previous]) = trace.back() {
+                    if trigger.edge.crossed(*previous, value, trigger.threshold) {
+                        trigger.fired = Some(time);
+                    }
+                }
+            }
+            trace.push_back([time, value]);
+        }
+
+        if let Some(fired) = self.trigger.fired {
+            if time - fired >= self.window / 2f64 {
+                self.frozen = true;
+                self.trigger.armed = false;
+            }
+        }
+
+        for trace in self.channels.values_mut() {
+            while trace.front().is_some_and(|[t, _]| time - t > self.window) {
+                trace.pop_front();
+            }
+        }
+        // inputs that went away, like a controller that was unassigned.
+        self.channels
+            .retain(|_, trace| trace.back().is_some_and(|[t, _]| time - t <= self.window));
+    }
+
+    fn arm(&mut self) {
+        self.trigger.armed = true;
+        self.trigger.fired = None;
+        self.frozen = false;
+    }
+
+    pub(crate) fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Window");
+            ui.add(
+                DragValue::new(&mut self.window)
+                    .speed(0.1)
+                    .clamp_range(1.0..=60.0)
+                    .suffix(" s"),
+            );
+
+            ui.separator();
+
+            if self.frozen {
+                if ui.button("▶ Run").clicked() {
+                    self.frozen = false;
+                    self.trigger.armed = false;
+                    self.trigger.fired = None;
+                }
+            } else if ui.button("⏸ Freeze").clicked() {
+                self.frozen = true;
+            }
+        });
+
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Trigger");
+            let channel: String = self
+                .trigger
+                .channel
+                .clone()
+                .unwrap_or_else(|| "None".to_string());
+            egui::ComboBox::from_id_source("oscilloscope_trigger_channel")
+                .selected_text(channel)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.trigger.channel, None, "None");
+                    for (_, name) in self.channels.keys() {
+                        ui.selectable_value(
+                            &mut self.trigger.channel,
+                            Some(name.clone()),
+                            name.as_str(),
+                        );
+                    }
+                });
+            egui::ComboBox::from_id_source("oscilloscope_trigger_edge")
+                .selected_text(edge_name(self.trigger.edge))
+                .show_ui(ui, |ui| {
+                    for edge in Edge::ALL {
+                        ui.selectable_value(&mut self.trigger.edge, edge, edge_name(edge));
+                    }
+                });
+            ui.add(
+                DragValue::new(&mut self.trigger.threshold)
+                    .speed(0.01)
+                    .clamp_range(-1.0..=1.0),
+            );
+            if ui
+                .add_enabled(self.trigger.channel.is_some(), egui::Button::new("🎯 Arm"))
+                .on_hover_text("Freezes half a window after the channel crosses the threshold.")
+                .clicked()
+            {
+                self.arm();
+            }
+            if self.trigger.armed {
+                ui.label(if self.trigger.fired.is_some() {
+                    "Triggered, capturing…"
+                } else {
+                    "Armed"
+                });
+            } else if self.frozen && self.trigger.fired.is_some() {
+                ui.label("Captured");
+            }
+        });
+
+        ui.separator();
+
+        let height: f32 =
+            ((ui.available_height() - 2f32 * ui.spacing().item_spacing.y) / 3f32).max(120f32);
+        for (group, name) in [
+            (Group::Axes, "oscilloscope_axes"),
+            (Group::Buttons, "oscilloscope_buttons"),
+            (Group::Outputs, "oscilloscope_outputs"),
+        ] {
+            egui_plot::Plot::new(name)
+                .height(height)
+                .link_axis("oscilloscope", true, false)
+                .include_y(if group == Group::Buttons { 0.0 } else { -1.0 })
+                .include_y(1.0)
+                .legend(Legend::default())
+                .allow_drag(self.frozen)
+                .allow_zoom(self.frozen)
+                .allow_scroll(self.frozen)
+                .show(ui, |plot_ui| {
+                    for ((_, channel), trace) in
+                        self.channels.iter().filter(|((g, _), _)| *g == group)
+                    {
+                        let points: PlotPoints = trace.iter().copied().collect();
+                        plot_ui.line(Line::new(points).name(channel));
+                    }
+                    if let Some(fired) = self.trigger.fired {
+                        plot_ui.vline(VLine::new(fired).name("Trigger"));
+                    }
+                });
+        }
+    }
+}
+
+impl Shared<Oscilloscope> {
+    /// Samples the inputs of both roles and the output of this step, called by the control loop.
+    pub(crate) fn sample(&mut self, now: SystemTime, joints: &JointState, output: (f64, f64)) {
+        let values: Vec<((Group, String), f64)> = Oscilloscope::values(joints, output);
+        self.update(move |oscilloscope| oscilloscope.push(now, values));
+    }
+}
+
+fn edge_name(edge: Edge) -> &'static str {
+    match edge {
+        Edge::Rising => "↗ Rising",
+        Edge::Falling => "↘ Falling",
+        Edge::Either => "↕ Either",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn left(scope: &Oscilloscope) -> Vec<[f64; 2]> {
+        scope.channels[&(Group::Outputs, "Left".to_string())]
+            .iter()
+            .copied()
+            .collect()
+    }
+
+    fn push(scope: &mut Oscilloscope, seconds: f64, value: f64) {
+        let now: SystemTime = SystemTime::UNIX_EPOCH + Duration::from_secs_f64(seconds);
+        scope.push(
+            now,
+            Oscilloscope::values(&JointState::default(), (value, 0f64)),
+        );
+    }
+
+    #[test]
+    fn edges_cross_the_threshold() {
+        assert!(Edge::Rising.crossed(0.2, 0.5, 0.5));
+        assert!(!Edge::Rising.crossed(0.5, 0.8, 0.5));
+        assert!(!Edge::Rising.crossed(0.8, 0.2, 0.5));
+        assert!(Edge::Falling.crossed(0.8, 0.2, 0.5));
+        assert!(Edge::Either.crossed(0.8, 0.2, 0.5) && Edge::Either.crossed(0.2, 0.8, 0.5));
+    }
+
+    #[test]
+    fn samples_older_than_the_window_scroll_out() {
+        let mut scope: Oscilloscope = Oscilloscope {
+            window: 1.0,
+            ..Oscilloscope::default()
+        };
+        for step in 0..5 {
+            push(&mut scope, step as f64 * 0.5, step as f64);
+        }
+        assert_eq!(left(&scope), [[1.0, 2.0], [1.5, 3.0], [2.0, 4.0]]);
+    }
+
+    #[test]
+    fn trigger_freezes_half_a_window_after_the_crossing() {
+        let mut scope: Oscilloscope = Oscilloscope {
+            window: 1.0,
+            trigger: Trigger {
+                channel: Some("Left".to_string()),
+                edge: Edge::Rising,
+                ..Trigger::default()
+            },
+            ..Oscilloscope::default()
+        };
+        scope.arm();
+        push(&mut scope, 0.0, 0.0);
+        push(&mut scope, 0.25, 1.0);
+        push(&mut scope, 0.5, 1.0);
+        assert!(!scope.frozen);
+        push(&mut scope, 0.75, 0.0);
+        assert!(scope.frozen);
+
+        push(&mut scope, 1.0, 1.0);
+        assert_eq!(left(&scope).len(), 4);
+    }
+}
@@ -0,0 +1,374 @@
+use std::collections::BTreeSet;
+use std::time::{Duration, SystemTime};
+
+use crate::input::binding::ControllerModel;
+use crate::input::event::InputEvent;
+use crate::input::role::Role;
+use crate::input::source::{Device, InputSource, SourceEvent};
+
+/// Something that reached the joint state, in the order it happened.
+// only verifying reads the safety steps.
+#[cfg_attr(not(feature = "serde"), allow(dead_code))]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub(crate) enum RecordedStep {
+    // controller event after calibration, with the roles of the controller that sent it.
+    Event {
+        roles: Vec<Role>,
+        model: Option<ControllerModel>,
+        event: InputEvent,
+    },
+    // control loop step and the drive output it produced.
+    Tick {
+        time: SystemTime,
+        output: (f64, f64),
+    },
+    Released(Role),
+    Lost(Role),
+    Reacquired(Role),
+    EStopReset,
+    Armed,
+}
+
+/// Timestamped input of a session and the drive outputs it produced.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub(crate) struct Recording {
+    pub(crate) start: SystemTime,
+    // joint state settings when recording started, replays start from them.
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    pub(crate) settings: String,
+    // safety state when recording started, only verifying starts from it.
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    pub(crate) estop: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    pub(crate) disarmed: bool,
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    pub(crate) lost: BTreeSet<Role>,
+    pub(crate) steps: Vec<RecordedStep>,
+}
+
+impl Recording {
+    #[cfg(feature = "serde")]
+    pub(crate) fn parse(contents: &str) -> Result<Recording, String> {
+        ron::from_str(contents).map_err(|error| error.to_string())
+    }
+
+    fn duration(&self) -> Duration {
+        self.steps
+            .iter()
+            .rev()
+            .find_map(|step| match step {
+                RecordedStep::Tick { time, .. } => time.duration_since(self.start).ok(),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn outputs(&self) -> Vec<(f64, f64)> {
+        self.steps
+            .iter()
+            .filter_map(|step| match step {
+                RecordedStep::Tick { output, .. } => Some(*output),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Plays the controller events of a recording at their recorded offsets from the first poll.
+pub(crate) struct ReplaySource {
+    recording: Recording,
+    // taken from the first poll, like a script's.
+    start: Option<SystemTime>,
+    // index of the next step to play.
+    next: usize,
+}
+
+impl ReplaySource {
+    fn new(recording: Recording) -> Self {
+        Self {
+            recording,
+            start: None,
+            next: 0,
+        }
+    }
+
+    pub(crate) fn finished(&self) -> bool {
+        self.next >= self.recording.steps.len()
+    }
+}
+
+impl InputSource for ReplaySource {
+    fn poll(&mut self, now: SystemTime) -> Vec<SourceEvent> {
+        let start: SystemTime = *self.start.get_or_insert(now);
+        let elapsed: Duration = now.duration_since(start).unwrap_or_default();
+        let recorded: SystemTime = self.recording.start;
+        let offset =
+            |time: SystemTime| -> Duration { time.duration_since(recorded).unwrap_or_default() };
+
+        let mut events: Vec<SourceEvent> = Vec::new();
+        while let Some(step) = self.recording.steps.get(self.next) {
+            match step {
+                RecordedStep::Event {
+                    roles,
+                    model,
+                    event,
+                } => {
+                    if offset(event.time) > elapsed {
+                        break;
+                    }
+                    events.push(SourceEvent {
+                        device: Device::replayed(roles),
+                        model: *model,
+                        event: InputEvent {
+                            time: start + offset(event.time),
+                            ..*event
+                        },
+                    });
+                }
+                RecordedStep::Tick { time, .. } if offset(*time) > elapsed => break,
+                // ticks are left to the live loop, safety steps belong to the recording's controllers.
+                _ => (),
+            }
+            self.next += 1;
+        }
+        events
+    }
+
+    fn devices(&self) -> Vec<(Device, String)> {
+        let mut devices: Vec<(Device, String)> = Vec::new();
+        for step in &self.recording.steps {
+            if let RecordedStep::Event { roles, .. } = step {
+                let device: Device = Device::replayed(roles);
+                if devices.iter().all(|(d, _)| *d != device) {
+                    let names: Vec<String> = roles.iter().map(Role::to_string).collect();
+                    devices.push((device, format!("⏯ Replay {}", names.join(" "))));
+                }
+            }
+        }
+        devices
+    }
+}
+
+enum Mode {
+    Idle,
+    Recording(Recording),
+    // live input only reaches the e-stop while the recording plays.
+    Replaying(ReplaySource),
+}
+
+/// What the recording UI asks for, carried out on the control loop's state.
+pub(crate) enum RecorderRequest {
+    Record,
+    Replay,
+    Stop,
+    #[cfg(feature = "serde")]
+    Save,
+    #[cfg(feature = "serde")]
+    Verify,
+}
+
+/// What the recording UI shows, copied out of the recorder every frame.
+pub(crate) struct RecorderStatus {
+    // steps so far while recording.
+    recording: Option<usize>,
+    // replayed and total steps while replaying.
+    replaying: Option<(usize, usize)>,
+    // steps and length of the latest recording.
+    last: Option<(usize, Duration)>,
+    report: Option<Result<String, String>>,
+}
+
+/// Records what feeds the joint state and plays it back.
+pub(crate) struct Recorder {
+    mode: Mode,
+    // latest finished or loaded recording.
+    pub(crate) last: Option<Recording>,
+    // outcome of the latest save, load or verification.
+    pub(crate) report: Option<Result<String, String>>,
+}
+
+impl Default for Recorder {
+    fn default() -> Self {
+        Self {
+            mode: Mode::Idle,
+            last: None,
+            report: None,
+        }
+    }
+}
+
+impl Recorder {
+    pub(crate) fn replaying(&self) -> bool {
+        matches!(self.mode, Mode::Replaying(_))
+    }
+
+    pub(crate) fn start(
+        &mut self,
+        settings: String,
+        estop: bool,
+        disarmed: bool,
+        lost: BTreeSet<Role>,
+        now: SystemTime,
+    ) {
+        self.mode = Mode::Recording(Recording {
+            start: now,
+            settings,
+            estop,
+            disarmed,
+            lost,
+            steps: Vec::new(),
+        });
+    }
+
+    /// Ends a recording or a replay, only a recording is kept.
+    pub(crate) fn stop(&mut self) {
+        if let Mode::Recording(recording) = std::mem::replace(&mut self.mode, Mode::Idle) {
+            self.last = Some(recording);
+        }
+    }
+
+    pub(crate) fn record(&mut self, step: RecordedStep) {
+        if let Mode::Recording(recording) = &mut self.mode {
+            recording.steps.push(step);
+        }
+    }
+
+    /// Completes the latest tick with the output computed after it.
+    pub(crate) fn output(&mut self, output: (f64, f64)) {
+        if let Mode::Recording(recording) = &mut self.mode {
+            if let Some(RecordedStep::Tick {
+                output: recorded, ..
+            }) = recording.steps.last_mut()
+            {
+                *recorded = output;
+            }
+        }
+    }
+
+    /// Stops a replay, live input was e-stopped.
+    pub(crate) fn abort(&mut self) {
+        if self.replaying() {
+            self.mode = Mode::Idle;
+            self.report = Some(Err("Replay aborted by the e-stop".to_string()));
+        }
+    }
+
+    pub(crate) fn replay(&mut self, recording: Recording) {
+        self.mode = Mode::Replaying(ReplaySource::new(recording));
+    }
+
+    /// The replay to poll next to the live sources.
+    pub(crate) fn source(&mut self) -> Option<&mut ReplaySource> {
+        match &mut self.mode {
+            Mode::Replaying(source) => Some(source),
+            _ => None,
+        }
+    }
+
+    /// Writes `recording` to a new file and returns the report.
+    #[cfg(feature = "serde")]
+    pub(crate) fn save(recording: &Recording) -> Result<String, String> {
+        let name: String = crate::file::stamped("recording.ron", recording.start);
+        ron::ser::to_string_pretty(recording, ron::ser::PrettyConfig::default())
+            .map_err(|error| error.to_string())
+            .and_then(|contents| crate::file::save(&name, &contents))
+            .map(|()| format!("Saved {name}"))
+    }
+
+    /// Takes a recording parsed from the file `name`.
+    #[cfg(feature = "serde")]
+    pub(crate) fn loaded(&mut self, name: &str, recording: Result<Recording, String>) {
+        match recording {
+            Ok(recording) => {
+                self.last = Some(recording);
+                self.report = Some(Ok(format!("Loaded {name}")));
+            }
+            Err(error) => self.report = Some(Err(format!("{name}: {error}"))),
+        }
+    }
+
+    pub(crate) fn status(&self) -> RecorderStatus {
+        RecorderStatus {
+            recording: match &self.mode {
+                Mode::Recording(recording) => Some(recording.steps.len()),
+                _ => None,
+            },
+            replaying: match &self.mode {
+                Mode::Replaying(source) => Some((source.next, source.recording.steps.len())),
+                _ => None,
+            },
+            last: self
+                .last
+                .as_ref()
+                .map(|recording| (recording.steps.len(), recording.duration())),
+            report: self.report.clone(),
+        }
+    }
+}
+
+impl RecorderStatus {
+    pub(crate) fn ui(&self, ui: &mut egui::Ui) -> Option<RecorderRequest> {
+        let mut request: Option<RecorderRequest> = None;
+        ui.horizontal(|ui| match (self.recording, self.replaying) {
+            (Some(steps), _) => {
+                if ui.button("⏹ Stop").clicked() {
+                    request = Some(RecorderRequest::Stop);
+                }
+                ui.label(format!("{steps} steps"));
+            }
+            (_, Some((next, steps))) => {
+                if ui.button("⏹ Stop").clicked() {
+                    request = Some(RecorderRequest::Stop);
+                }
+                ui.label(format!("{next}/{steps} steps"));
+            }
+            (None, None) => {
+                if ui.button("⏺ Record").clicked() {
+                    request = Some(RecorderRequest::Record);
+                }
+                ui.add_enabled_ui(self.last.is_some(), |ui| {
+                    if ui
+                        .button("▶ Replay")
+                        .on_hover_text("Plays the recording on its settings and timing, controllers can only e-stop meanwhile.")
+                        .clicked()
+                    {
+                        request = Some(RecorderRequest::Replay);
+                    }
+                    #[cfg(feature = "serde")]
+                    {
+                        if ui
+                            .button("✔ Verify")
+                            .on_hover_text("Replays the recording from its settings and compares every drive output.")
+                            .clicked()
+                        {
+                            request = Some(RecorderRequest::Verify);
+                        }
+                        if ui.button("💾 Save").clicked() {
+                            request = Some(RecorderRequest::Save);
+                        }
+                    }
+                });
+            }
+        });
+
+        if let Some((steps, duration)) = self.last {
+            ui.label(format!("{steps} steps, {:.1} s", duration.as_secs_f64()));
+        }
+        #[cfg(feature = "serde")]
+        ui.label("Drop a recording file on the window to load it.");
+        match &self.report {
+            Some(Ok(report)) => {
+                ui.label(report);
+            }
+            Some(Err(error)) => {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+            None => (),
+        }
+        request
+    }
+}
@@ -0,0 +1,154 @@
+use std::fmt::{Display, Formatter};
+
+use egui::DragValue;
+
+/// How gamepad inputs are mapped onto the left and right drive outputs.
+#[derive(Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub(crate) enum DriveScheme {
+    // left stick, forward on Y and turn on X.
+    #[default]
+    Arcade,
+    // right trigger throttle, left trigger reverse or brake, left stick X steering.
+    Trigger,
+    // left stick Y drives the left side, right stick Y the right side.
+    Tank,
+}
+
+impl DriveScheme {
+    const ALL: [DriveScheme; 3] = [DriveScheme::Arcade, DriveScheme::Trigger, DriveScheme::Tank];
+
+    fn next(self) -> Self {
+        let index: usize = Self::ALL
+            .iter()
+            .position(|s| *s == self)
+            .unwrap_or_default();
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+impl Display for DriveScheme {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DriveScheme::Arcade => write!(f, "🕹 Arcade"),
+            DriveScheme::Trigger => write!(f, "🏁 Trigger"),
+            DriveScheme::Tank => write!(f, "🛡 Tank"),
+        }
+    }
+}
+
+/// Pulls both tank sides together when the sticks are almost level, which makes driving straight easier.
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub(crate) struct TankCoupling {
+    enabled: bool,
+    // how far both sides are pulled towards their average, in [0, 1].
+    strength: f64,
+    // side difference below which the assist kicks in, fading out towards it.
+    window: f64,
+}
+
+impl Default for TankCoupling {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            strength: 1.0,
+            window: 0.2,
+        }
+    }
+}
+
+impl TankCoupling {
+    pub(crate) fn apply(&self, (left, right): (f64, f64)) -> (f64, f64) {
+        let difference: f64 = (right - left).abs();
+        if !self.enabled || self.window <= 0f64 || difference >= self.window {
+            return (left, right);
+        }
+
+        let average: f64 = (left + right) / 2f64;
+        let weight: f64 = self.strength * (1f64 - difference / self.window);
+
+        (
+            left + (average - left) * weight,
+            right + (average - right) * weight,
+        )
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.enabled, "Coupling Assist");
+            ui.add_enabled(
+                self.enabled,
+                DragValue::new(&mut self.strength)
+                    .speed(0.01)
+                    .clamp_range(0.0..=1.0)
+                    .prefix("strength "),
+            );
+            ui.add_enabled(
+                self.enabled,
+                DragValue::new(&mut self.window)
+                    .speed(0.01)
+                    .clamp_range(0.0..=2.0)
+                    .prefix("window "),
+            );
+        });
+    }
+}
+
+#[derive(Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub(crate) struct DriveSchemes {
+    pub(crate) selected: DriveScheme,
+    pub(crate) tank_coupling: TankCoupling,
+}
+
+impl DriveSchemes {
+    pub(crate) fn cycle(&mut self) {
+        self.selected = self.selected.next();
+    }
+
+    pub(crate) fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Scheme");
+            for scheme in DriveScheme::ALL {
+                ui.selectable_value(&mut self.selected, scheme, scheme.to_string());
+            }
+        });
+
+        if self.selected == DriveScheme::Tank {
+            self.tank_coupling.ui(ui);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycles_through_every_scheme() {
+        let mut schemes: DriveSchemes = DriveSchemes::default();
+        schemes.cycle();
+        assert!(schemes.selected == DriveScheme::Trigger);
+        schemes.cycle();
+        schemes.cycle();
+        assert!(schemes.selected == DriveScheme::Arcade);
+    }
+
+    #[test]
+    fn coupling_pulls_nearly_level_sides_together() {
+        let coupling: TankCoupling = TankCoupling::default();
+        assert_eq!(coupling.apply((0.5, 0.5)), (0.5, 0.5));
+        // halfway into the window, half of the full pull.
+        assert_eq!(coupling.apply((0.45, 0.55)), (0.475, 0.525));
+        assert_eq!(coupling.apply((0.2, 0.8)), (0.2, 0.8));
+
+        let disabled: TankCoupling = TankCoupling {
+            enabled: false,
+            ..TankCoupling::default()
+        };
+        assert_eq!(disabled.apply((0.45, 0.55)), (0.45, 0.55));
+    }
+}
@@ -0,0 +1,213 @@
+use std::fmt::{Display, Formatter};
+
+use egui::{Color32, DragValue};
+
+#[derive(Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub(crate) enum SpeedMode {
+    Crawl,
+    #[default]
+    Normal,
+    Turbo,
+}
+
+impl SpeedMode {
+    const ALL: [SpeedMode; 3] = [SpeedMode::Crawl, SpeedMode::Normal, SpeedMode::Turbo];
+
+    fn up(self) -> Self {
+        match self {
+            SpeedMode::Crawl => SpeedMode::Normal,
+            SpeedMode::Normal | SpeedMode::Turbo => SpeedMode::Turbo,
+        }
+    }
+
+    fn down(self) -> Self {
+        match self {
+            SpeedMode::Crawl | SpeedMode::Normal => SpeedMode::Crawl,
+            SpeedMode::Turbo => SpeedMode::Normal,
+        }
+    }
+
+    pub(crate) fn color(&self) -> Color32 {
+        match self {
+            SpeedMode::Crawl => Color32::from_rgb(80, 160, 255),
+            SpeedMode::Normal => Color32::from_rgb(80, 200, 120),
+            SpeedMode::Turbo => Color32::from_rgb(255, 80, 60),
+        }
+    }
+}
+
+impl Display for SpeedMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpeedMode::Crawl => write!(f, "🐢 Crawl"),
+            SpeedMode::Normal => write!(f, "🚶 Normal"),
+            SpeedMode::Turbo => write!(f, "🚀 Turbo"),
+        }
+    }
+}
+
+/// Output envelope of a single speed mode.
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub(crate) struct SpeedModeSettings {
+    // fraction of the robot profile's max speeds, in [0, 1].
+    max_speed: f64,
+    // response curve exponent, 1 is linear and larger values give finer control near center.
+    curve: f64,
+}
+
+impl SpeedModeSettings {
+    /// Shapes the faster wheel by the curve and scales the other by the same factor, so the turn ratio is kept.
+    fn apply(&self, (left, right): (f64, f64)) -> (f64, f64) {
+        let magnitude: f64 = left.abs().max(right.abs());
+        if magnitude == 0f64 {
+            return (0f64, 0f64);
+        }
+        let scale: f64 = magnitude.powf(self.curve) / magnitude * self.max_speed;
+        (left * scale, right * scale)
+    }
+}
+
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub(crate) struct SpeedModes {
+    selected: SpeedMode,
+    crawl: SpeedModeSettings,
+    normal: SpeedModeSettings,
+    turbo: SpeedModeSettings,
+    // turbo is only engaged while the modifier is held.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    turbo_modifier_held: bool,
+}
+
+impl Default for SpeedModes {
+    fn default() -> Self {
+        Self {
+            selected: SpeedMode::Normal,
+            crawl: SpeedModeSettings {
+                max_speed: 0.25,
+                curve: 1.0,
+            },
+            normal: SpeedModeSettings {
+                max_speed: 0.6,
+                curve: 1.5,
+            },
+            turbo: SpeedModeSettings {
+                max_speed: 1.0,
+                curve: 2.0,
+            },
+            turbo_modifier_held: false,
+        }
+    }
+}
+
+impl SpeedModes {
+    pub(crate) fn gear_up(&mut self) {
+        self.selected = self.selected.up();
+    }
+
+    pub(crate) fn gear_down(&mut self) {
+        self.selected = self.selected.down();
+    }
+
+    pub(crate) fn hold_turbo_modifier(&mut self, held: bool) {
+        self.turbo_modifier_held = held;
+    }
+
+    /// The mode that is actually applied, turbo falls back to normal without the modifier.
+    pub(crate) fn active(&self) -> SpeedMode {
+        match self.selected {
+            SpeedMode::Turbo if !self.turbo_modifier_held => SpeedMode::Normal,
+            mode => mode,
+        }
+    }
+
+    pub(crate) fn turbo_armed(&self) -> bool {
+        self.selected == SpeedMode::Turbo && !self.turbo_modifier_held
+    }
+
+    fn settings(&self, mode: SpeedMode) -> &SpeedModeSettings {
+        match mode {
+            SpeedMode::Crawl => &self.crawl,
+            SpeedMode::Normal => &self.normal,
+            SpeedMode::Turbo => &self.turbo,
+        }
+    }
+
+    pub(crate) fn apply(&self, output: (f64, f64)) -> (f64, f64) {
+        self.settings(self.active()).apply(output)
+    }
+
+    pub(crate) fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Selected");
+            for mode in SpeedMode::ALL {
+                ui.selectable_value(&mut self.selected, mode, mode.to_string());
+            }
+        });
+
+        egui::Grid::new("speed_modes_grid")
+            .striped(true)
+            .num_columns(3)
+            .show(ui, |ui| {
+                ui.label("");
+                ui.strong("Max Speed");
+                ui.strong("Curve");
+                ui.end_row();
+
+                for (mode, settings) in [
+                    (SpeedMode::Crawl, &mut self.crawl),
+                    (SpeedMode::Normal, &mut self.normal),
+                    (SpeedMode::Turbo, &mut self.turbo),
+                ] {
+                    ui.label(mode.to_string());
+                    ui.add(
+                        DragValue::new(&mut settings.max_speed)
+                            .speed(0.01)
+                            .clamp_range(0.0..=1.0),
+                    );
+                    ui.add(
+                        DragValue::new(&mut settings.curve)
+                            .speed(0.01)
+                            .clamp_range(1.0..=5.0),
+                    );
+                    ui.end_row();
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turbo_needs_the_modifier() {
+        let mut modes: SpeedModes = SpeedModes::default();
+        modes.gear_up();
+        assert!(modes.active() == SpeedMode::Normal && modes.turbo_armed());
+        assert_eq!(modes.apply((1.0, 1.0)), (0.6, 0.6));
+
+        modes.hold_turbo_modifier(true);
+        assert!(modes.active() == SpeedMode::Turbo && !modes.turbo_armed());
+        assert_eq!(modes.apply((1.0, -1.0)), (1.0, -1.0));
+
+        modes.gear_down();
+        modes.gear_down();
+        assert!(modes.active() == SpeedMode::Crawl);
+        assert_eq!(modes.apply((1.0, -1.0)), (0.25, -0.25));
+    }
+
+    #[test]
+    fn curve_keeps_the_turn_ratio() {
+        let settings: SpeedModeSettings = SpeedModeSettings {
+            max_speed: 0.5,
+            curve: 2.0,
+        };
+        assert_eq!(settings.apply((0.5, 0.25)), (0.125, 0.0625));
+        assert_eq!(settings.apply((-0.5, 0.25)), (-0.125, 0.0625));
+        assert_eq!(settings.apply((0.0, 0.0)), (0.0, 0.0));
+    }
+}
@@ -0,0 +1,67 @@
+/// Straight joint-space move where every joint arrives at the same time without exceeding its
+/// velocity limit.
+#[derive(Clone)]
+pub(crate) struct JointTrajectory {
+    pub(crate) name: String,
+    start: Vec<f64>,
+    goal: Vec<f64>,
+    // s.
+    duration: f64,
+    elapsed: f64,
+}
+
+impl JointTrajectory {
+    pub(crate) fn new(name: &str, start: Vec<f64>, goal: Vec<f64>, max_velocities: &[f64]) -> Self {
+        // joints that aren't allowed to move hold their start.
+        let goal: Vec<f64> = start
+            .iter()
+            .zip(&goal)
+            .zip(max_velocities.iter().chain(std::iter::repeat(&0f64)))
+            .map(|((start, goal), max_velocity)| if *max_velocity > 0f64 { *goal } else { *start })
+            .collect();
+        // the slowest joint sets the pace for all of them.
+        let duration: f64 = start
+            .iter()
+            .zip(&goal)
+            .zip(max_velocities)
+            .filter(|(_, max_velocity)| **max_velocity > 0f64)
+            .map(|((start, goal), max_velocity)| (goal - start).abs() / max_velocity)
+            .fold(0f64, f64::max);
+
+        Self {
+            name: name.to_string(),
+            start,
+            goal,
+            duration,
+            elapsed: 0f64,
+        }
+    }
+
+    /// Advances by `dt` seconds and returns the positions to command.
+    pub(crate) fn advance(&mut self, dt: f64) -> Vec<f64> {
+        self.elapsed = (self.elapsed + dt).min(self.duration);
+        let fraction: f64 = self.progress();
+
+        self.start
+            .iter()
+            .zip(&self.goal)
+            .map(|(start, goal)| start + (goal - start) * fraction)
+            .collect()
+    }
+
+    pub(crate) fn progress(&self) -> f64 {
+        if self.duration > 0f64 {
+            self.elapsed / self.duration
+        } else {
+            1f64
+        }
+    }
+
+    pub(crate) fn remaining(&self) -> f64 {
+        self.duration - self.elapsed
+    }
+
+    pub(crate) fn finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+}
@@ -0,0 +1,257 @@
+use std::f64::consts::PI;
+use std::time::{Duration, SystemTime};
+
+use egui::RichText;
+
+use crate::robot::profile::DriveProfile;
+
+/// A command older than this is stale, the robot stops instead of executing it.
+pub(crate) const WATCHDOG: Duration = Duration::from_millis(250);
+
+/// Body velocity of the drive base.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct Twist {
+    // m/s, positive forward.
+    pub(crate) linear: f64,
+    // rad/s, positive counter-clockwise.
+    pub(crate) angular: f64,
+}
+
+/// Rotational speed of a single wheel.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct WheelVelocity {
+    // rad/s.
+    pub(crate) angular: f64,
+}
+
+impl WheelVelocity {
+    pub(crate) fn rpm(&self) -> f64 {
+        self.angular * 60f64 / (2f64 * PI)
+    }
+}
+
+/// A drive setpoint in both normalized and physical units.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct DriveCommand {
+    // mixed left and right outputs in [-1, 1].
+    pub(crate) normalized: (f64, f64),
+    pub(crate) twist: Twist,
+    pub(crate) left_wheel: WheelVelocity,
+    pub(crate) right_wheel: WheelVelocity,
+    // when the control loop computed it.
+    pub(crate) time: Option<SystemTime>,
+}
+
+impl DriveCommand {
+    pub(crate) fn new(normalized: (f64, f64), profile: &DriveProfile, time: SystemTime) -> Self {
+        let left: f64 = normalized.0.clamp(-1f64, 1f64);
+        let right: f64 = normalized.1.clamp(-1f64, 1f64);
+
+        // forward intent is the common part of both sides, turn intent the difference.
+        let mut twist: Twist = Twist {
+            linear: (left + right) / 2f64 * profile.max_linear_speed,
+            angular: (right - left) / 2f64 * profile.max_angular_speed,
+        };
+
+        // differential drive inverse kinematics.
+        let half_track: f64 = profile.track_width / 2f64;
+        let mut left_wheel: WheelVelocity = WheelVelocity {
+            angular: (twist.linear - twist.angular * half_track) / profile.wheel_radius,
+        };
+        let mut right_wheel: WheelVelocity = WheelVelocity {
+            angular: (twist.linear + twist.angular * half_track) / profile.wheel_radius,
+        };
+
+        // no wheel can outrun full forward speed, slow the whole twist down so the path stays.
+        let max_wheel: f64 = profile.max_linear_speed / profile.wheel_radius;
+        let fastest: f64 = left_wheel.angular.abs().max(right_wheel.angular.abs());
+        if fastest > max_wheel {
+            let scale: f64 = max_wheel / fastest;
+            twist.linear *= scale;
+            twist.angular *= scale;
+            left_wheel.angular *= scale;
+            right_wheel.angular *= scale;
+        }
+
+        Self {
+            normalized: (left, right),
+            twist,
+            left_wheel,
+            right_wheel,
+            time: Some(time),
+        }
+    }
+
+    /// Whether the control loop stopped refreshing the command.
+    pub(crate) fn expired(&self, now: SystemTime) -> bool {
+        self.time
+            .is_some_and(|time| now.duration_since(time).unwrap_or_default() > WATCHDOG)
+    }
+
+    /// The command to execute at `now`, zero once it expired.
+    pub(crate) fn current(&self, now: SystemTime) -> Self {
+        if self.expired(now) {
+            Self::default()
+        } else {
+            *self
+        }
+    }
+
+    pub(crate) fn ui(&self, ui: &mut egui::Ui) {
+        egui::Grid::new("drive_command_grid")
+            .striped(true)
+            .num_columns(3)
+            .show(ui, |ui| {
+                ui.label("");
+                ui.strong("Normalized");
+                ui.strong("Physical");
+                ui.end_row();
+
+                let (left, right) = self.normalized;
+
+                ui.label("Linear");
+                ui.label(monospace(format!("{:+.3}", (left + right) / 2f64)));
+                ui.label(monospace(format!("{:+.3} m/s", self.twist.linear)));
+                ui.end_row();
+
+                ui.label("Angular");
+                ui.label(monospace(format!("{:+.3}", (right - left) / 2f64)));
+                ui.label(monospace(format!("{:+.3} rad/s", self.twist.angular)));
+                ui.end_row();
+
+                for (name, normalized, wheel) in [
+                    ("Left Wheel", self.normalized.0, self.left_wheel),
+                    ("Right Wheel", self.normalized.1, self.right_wheel),
+                ] {
+                    ui.label(name);
+                    ui.label(monospace(format!("{normalized:+.3}")));
+                    ui.label(monospace(format!(
+                        "{:+.2} rad/s  {:+.1} RPM",
+                        wheel.angular,
+                        wheel.rpm()
+                    )));
+                    ui.end_row();
+                }
+            });
+    }
+}
+
+/// Pose of the drive base dead-reckoned from the commanded twist, relative to where it was reset.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct Odometry {
+    // m.
+    pub(crate) x: f64,
+    pub(crate) y: f64,
+    // rad, counter-clockwise from the starting direction.
+    pub(crate) heading: f64,
+    // m, travelled in either direction.
+    pub(crate) distance: f64,
+}
+
+impl Odometry {
+    /// Moves along `twist` for `dt` seconds.
+    pub(crate) fn integrate(&mut self, twist: Twist, dt: f64) {
+        // the heading halfway through the step follows the arc closer than the initial one.
+        let heading: f64 = self.heading + twist.angular * dt / 2f64;
+        self.x += twist.linear * dt * heading.cos();
+        self.y += twist.linear * dt * heading.sin();
+        self.heading = (self.heading + twist.angular * dt).rem_euclid(2f64 * PI);
+        self.distance += (twist.linear * dt).abs();
+    }
+
+    pub(crate) fn ui(&self, ui: &mut egui::Ui) {
+        egui::Grid::new("odometry_grid")
+            .striped(true)
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("Position");
+                ui.label(monospace(format!("{:+.2} m  {:+.2} m", self.x, self.y)));
+                ui.end_row();
+
+                ui.label("Heading");
+                ui.label(monospace(format!("{:.1}°", self.heading.to_degrees())));
+                ui.end_row();
+
+                ui.label("Distance");
+                ui.label(monospace(format!("{:.2} m", self.distance)));
+                ui.end_row();
+            });
+    }
+}
+
+fn monospace(text: String) -> RichText {
+    RichText::new(text).monospace()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(normalized: (f64, f64), max_angular_speed: f64) -> DriveCommand {
+        let profile: DriveProfile = DriveProfile {
+            max_angular_speed,
+            ..DriveProfile::default()
+        };
+        DriveCommand::new(normalized, &profile, SystemTime::UNIX_EPOCH)
+    }
+
+    // forward kinematics of the wheel speeds.
+    fn wheel_twist(command: &DriveCommand) -> Twist {
+        let profile: DriveProfile = DriveProfile::default();
+        let (left, right) = (
+            command.left_wheel.angular * profile.wheel_radius,
+            command.right_wheel.angular * profile.wheel_radius,
+        );
+        Twist {
+            linear: (left + right) / 2f64,
+            angular: (right - left) / profile.track_width,
+        }
+    }
+
+    #[test]
+    fn wheel_speeds_match_the_twist() {
+        for normalized in [(1.0, 1.0), (-0.5, 0.5), (1.0, 0.2), (-1.0, -0.3)] {
+            let command: DriveCommand = command(normalized, 2.0);
+            let twist: Twist = wheel_twist(&command);
+            assert!((twist.linear - command.twist.linear).abs() < 1e-12);
+            assert!((twist.angular - command.twist.angular).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn odometry_follows_a_square() {
+        let mut odometry: Odometry = Odometry::default();
+        for _ in 0..4 {
+            odometry.integrate(
+                Twist {
+                    linear: 1.0,
+                    angular: 0.0,
+                },
+                2.0,
+            );
+            odometry.integrate(
+                Twist {
+                    linear: 0.0,
+                    angular: PI / 2f64,
+                },
+                1.0,
+            );
+        }
+
+        assert!(odometry.x.abs() < 1e-9 && odometry.y.abs() < 1e-9);
+        assert!(odometry.heading.abs() < 1e-9 || (odometry.heading - 2f64 * PI).abs() < 1e-9);
+        assert_eq!(odometry.distance, 8.0);
+    }
+
+    #[test]
+    fn fast_turns_are_limited_by_the_wheels() {
+        // spinning in place at 10 rad/s would need the wheels at 2.5 m/s.
+        let command: DriveCommand = command((-1.0, 1.0), 10.0);
+        let max_wheel: f64 = 1.0 / 0.1;
+
+        assert!((command.right_wheel.angular - max_wheel).abs() < 1e-12);
+        assert!((command.left_wheel.angular + max_wheel).abs() < 1e-12);
+        assert!((command.twist.angular - 4.0).abs() < 1e-12);
+        assert!((wheel_twist(&command).angular - command.twist.angular).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,414 @@
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use egui::{Color32, DragValue, RichText};
+use gilrs_core::utils::time_now;
+
+use crate::command::control_loop::Shared;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub(crate) enum Severity {
+    Debug,
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    const ALL: [Severity; 4] = [
+        Severity::Debug,
+        Severity::Info,
+        Severity::Warning,
+        Severity::Error,
+    ];
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Debug => write!(f, "DEBUG"),
+            Severity::Info => write!(f, "INFO"),
+            Severity::Warning => write!(f, "WARN"),
+            Severity::Error => write!(f, "ERROR"),
+        }
+    }
+}
+
+/// Subsystem an entry is about.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub(crate) enum LogKind {
+    // button and axis events.
+    Gamepad,
+    // controllers appearing and disappearing.
+    Connection,
+    // e-stop, arming, odometry resets and lost controllers.
+    Safety,
+    // control loop health.
+    Telemetry,
+}
+
+impl LogKind {
+    const ALL: [LogKind; 4] = [
+        LogKind::Gamepad,
+        LogKind::Connection,
+        LogKind::Safety,
+        LogKind::Telemetry,
+    ];
+}
+
+impl Display for LogKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogKind::Gamepad => write!(f, "🎮 Gamepad"),
+            LogKind::Connection => write!(f, "🔌 Connection"),
+            LogKind::Safety => write!(f, "⛔ Safety"),
+            LogKind::Telemetry => write!(f, "📈 Telemetry"),
+        }
+    }
+}
+
+pub(crate) struct LogEntry {
+    // increasing, survives older entries being dropped.
+    id: u64,
+    time: SystemTime,
+    // device or part of the app that produced the entry.
+    source: String,
+    severity: Severity,
+    kind: LogKind,
+    payload: String,
+}
+
+impl LogEntry {
+    fn millis(&self) -> u128 {
+        self.time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis()
+    }
+
+    /// Time of day in UTC.
+    fn clock(&self) -> String {
+        let millis: u128 = self.millis();
+        let seconds: u128 = millis / 1000;
+        format!(
+            "{:02}:{:02}:{:02}.{:03}",
+            seconds / 3600 % 24,
+            seconds / 60 % 60,
+            seconds % 60,
+            millis % 1000
+        )
+    }
+}
+
+/// Which entries are shown and exported.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+struct LogFilter {
+    severity: Severity,
+    kinds: Vec<LogKind>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    search: String,
+}
+
+impl Default for LogFilter {
+    fn default() -> Self {
+        Self {
+            severity: Severity::Info,
+            kinds: LogKind::ALL.to_vec(),
+            search: String::new(),
+        }
+    }
+}
+
+impl LogFilter {
+    fn matches(&self, entry: &LogEntry) -> bool {
+        let search: String = self.search.to_lowercase();
+        entry.severity >= self.severity
+            && self.kinds.contains(&entry.kind)
+            && (search.is_empty()
+                || entry.source.to_lowercase().contains(&search)
+                || entry.payload.to_lowercase().contains(&search))
+    }
+}
+
+/// Events of every subsystem, newest last.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub(crate) struct EventLog {
+    // oldest entries are dropped beyond it.
+    capacity: usize,
+    filter: LogFilter,
+    // scrolls along with new entries.
+    follow: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    entries: VecDeque<LogEntry>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    next_id: u64,
+    // id of the newest entry shown while paused, logging goes on meanwhile.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    paused: Option<u64>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    export_error: Option<String>,
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self {
+            capacity: 5000,
+            filter: LogFilter::default(),
+            follow: true,
+            entries: VecDeque::new(),
+            next_id: 0,
+            paused: None,
+            export_error: None,
+        }
+    }
+}
+
+impl EventLog {
+    pub(crate) fn push(
+        &mut self,
+        time: SystemTime,
+        source: impl Into<String>,
+        severity: Severity,
+        kind: LogKind,
+        payload: impl Into<String>,
+    ) {
+        self.entries.push_back(LogEntry {
+            id: self.next_id,
+            time,
+            source: source.into(),
+            severity,
+            kind,
+            payload: payload.into(),
+        });
+        self.next_id += 1;
+        while self.entries.len() > self.capacity.max(1) {
+            self.entries.pop_front();
+        }
+    }
+
+    fn visible(&self) -> Vec<&LogEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| self.paused.is_none_or(|paused| entry.id <= paused))
+            .filter(|entry| self.filter.matches(entry))
+            .collect()
+    }
+
+    fn csv(&self) -> String {
+        let escape = |field: &str| -> String { format!("\"{}\"", field.replace('"', "\"\"")) };
+        let mut csv: String = "time_ms,severity,kind,source,payload\n".to_string();
+        for entry in self.visible() {
+            csv += &format!(
+                "{},{},{:?},{},{}\n",
+                entry.millis(),
+                entry.severity,
+                entry.kind,
+                escape(&entry.source),
+                escape(&entry.payload)
+            );
+        }
+        csv
+    }
+
+    fn json(&self) -> String {
+        let escape = |field: &str| -> String {
+            field
+                .chars()
+                .map(|c| match c {
+                    '"' => "\\\"".to_string(),
+                    '\\' => "\\\\".to_string(),
+                    '\n' => "\\n".to_string(),
+                    c if c.is_control() => format!("\\u{:04x}", c as u32),
+                    c => c.to_string(),
+                })
+                .collect()
+        };
+        let entries: Vec<String> = self
+            .visible()
+            .into_iter()
+            .map(|entry| {
+                format!(
+                    "  {{\"time_ms\": {}, \"severity\": \"{}\", \"kind\": \"{:?}\", \"source\": \"{}\", \"payload\": \"{}\"}}",
+                    entry.millis(),
+                    entry.severity,
+                    entry.kind,
+                    escape(&entry.source),
+                    escape(&entry.payload)
+                )
+            })
+            .collect();
+        format!("[\n{}\n]\n", entries.join(",\n"))
+    }
+
+    fn export(&mut self, name: &str, contents: String) {
+        let name: String = crate::file::stamped(name, time_now());
+        self.export_error = crate::file::save(&name, &contents).err();
+    }
+
+    pub(crate) fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal_wrapped(|ui| {
+            ui.heading("📝 Event Log");
+
+            ui.separator();
+
+            egui::ComboBox::from_id_source("event_log_severity")
+                .selected_text(format!("≥ {}", self.filter.severity))
+                .show_ui(ui, |ui| {
+                    for severity in Severity::ALL {
+                        ui.selectable_value(
+                            &mut self.filter.severity,
+                            severity,
+                            severity.to_string(),
+                        );
+                    }
+                });
+            for kind in LogKind::ALL {
+                let mut shown: bool = self.filter.kinds.contains(&kind);
+                if ui.toggle_value(&mut shown, kind.to_string()).changed() {
+                    self.filter.kinds.retain(|k| *k != kind);
+                    if shown {
+                        self.filter.kinds.push(kind);
+                    }
+                }
+            }
+            ui.add(
+                egui::TextEdit::singleline(&mut self.filter.search)
+                    .hint_text("🔍 Search")
+                    .desired_width(150f32),
+            );
+
+            ui.separator();
+
+            let mut paused: bool = self.paused.is_some();
+            if ui.toggle_value(&mut paused, "⏸ Pause").changed() {
+                self.paused = paused.then(|| self.next_id.saturating_sub(1));
+            }
+            ui.checkbox(&mut self.follow, "Follow");
+            if ui.button("🗑 Clear").clicked() {
+                self.entries.clear();
+            }
+            ui.label("Keep");
+            ui.add(
+                DragValue::new(&mut self.capacity)
+                    .speed(10.0)
+                    .clamp_range(100..=100_000),
+            );
+
+            ui.separator();
+
+            if ui.button("💾 CSV").clicked() {
+                self.export("event_log.csv", self.csv());
+            }
+            if ui.button("💾 JSON").clicked() {
+                self.export("event_log.json", self.json());
+            }
+            if let Some(error) = &self.export_error {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+        });
+
+        ui.separator();
+
+        let visible: Vec<&LogEntry> = self.visible();
+        let row_height: f32 = ui.text_style_height(&egui::TextStyle::Monospace);
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .stick_to_bottom(self.follow && self.paused.is_none())
+            .show_rows(ui, row_height, visible.len(), |ui, rows| {
+                for entry in &visible[rows] {
+                    let color: Color32 = match entry.severity {
+                        Severity::Debug => ui.visuals().weak_text_color(),
+                        Severity::Info => ui.visuals().text_color(),
+                        Severity::Warning => ui.visuals().warn_fg_color,
+                        Severity::Error => ui.visuals().error_fg_color,
+                    };
+                    ui.label(
+                        RichText::new(format!(
+                            "{} {:<5} {:<14} {:<20} {}",
+                            entry.clock(),
+                            entry.severity,
+                            entry.kind.to_string(),
+                            entry.source,
+                            entry.payload
+                        ))
+                        .monospace()
+                        .color(color),
+                    );
+                }
+            });
+    }
+}
+
+impl Shared<EventLog> {
+    /// Logs from the control loop, see [`EventLog::push`].
+    pub(crate) fn push(
+        &mut self,
+        time: SystemTime,
+        source: impl Into<String>,
+        severity: Severity,
+        kind: LogKind,
+        payload: impl Into<String>,
+    ) {
+        let (source, payload): (String, String) = (source.into(), payload.into());
+        self.update(move |log| log.push(time, source, severity, kind, payload));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn log(payload: &str) -> EventLog {
+        let mut log: EventLog = EventLog::default();
+        log.push(
+            UNIX_EPOCH + Duration::from_millis(1500),
+            "pad \"1\"",
+            Severity::Warning,
+            LogKind::Gamepad,
+            payload,
+        );
+        log
+    }
+
+    #[test]
+    fn csv_quotes_every_text_field() {
+        assert_eq!(
+            log("a, \"b\"\nc").csv(),
+            "time_ms,severity,kind,source,payload\n\
+             1500,WARN,Gamepad,\"pad \"\"1\"\"\",\"a, \"\"b\"\"\nc\"\n"
+        );
+    }
+
+    #[test]
+    fn json_escapes_quotes_backslashes_and_control_characters() {
+        assert_eq!(
+            log("a\\b\n\tc").json(),
+            "[\n  {\"time_ms\": 1500, \"severity\": \"WARN\", \"kind\": \"Gamepad\", \
+             \"source\": \"pad \\\"1\\\"\", \"payload\": \"a\\\\b\\n\\u0009c\"}\n]\n"
+        );
+    }
+
+    #[test]
+    fn oldest_entries_are_dropped_beyond_the_capacity() {
+        let mut log: EventLog = EventLog {
+            capacity: 2,
+            ..EventLog::default()
+        };
+        for payload in ["a", "b", "c"] {
+            log.push(UNIX_EPOCH, "", Severity::Info, LogKind::Gamepad, payload);
+        }
+        let payloads: Vec<&str> = log
+            .visible()
+            .into_iter()
+            .map(|entry| entry.payload.as_str())
+            .collect();
+        assert_eq!(payloads, ["b", "c"]);
+    }
+}
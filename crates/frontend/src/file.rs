@@ -0,0 +1,74 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// `name` with the seconds since the epoch before its extension, so saves don't collide.
+pub(crate) fn stamped(name: &str, time: SystemTime) -> String {
+    let seconds: u64 = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    match name.rsplit_once('.') {
+        Some((stem, extension)) => format!("{stem}_{seconds}.{extension}"),
+        None => format!("{name}_{seconds}"),
+    }
+}
+
+/// Writes a text file next to the app, the browser downloads it instead. Existing files are kept.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn save(name: &str, contents: &str) -> Result<(), String> {
+    use std::io::Write;
+
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(name)
+        .and_then(|mut file| file.write_all(contents.as_bytes()))
+        .map_err(|error| format!("Failed to write {name}: {error}"))
+}
+
+/// Writes a text file next to the app, the browser downloads it instead.
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn save(name: &str, contents: &str) -> Result<(), String> {
+    use eframe::web_sys;
+    use wasm_bindgen::{JsCast, JsValue};
+
+    let error = |error: JsValue| format!("Failed to download {name}: {error:?}");
+    let parts: web_sys::js_sys::Array = web_sys::js_sys::Array::of1(&JsValue::from_str(contents));
+    let blob: web_sys::Blob = web_sys::Blob::new_with_str_sequence(&parts).map_err(error)?;
+    let url: String = web_sys::Url::create_object_url_with_blob(&blob).map_err(error)?;
+    let anchor: web_sys::HtmlAnchorElement = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or_else(|| format!("Failed to download {name}: no document"))?
+        .create_element("a")
+        .map_err(error)?
+        .unchecked_into();
+    anchor.set_href(&url);
+    anchor.set_download(name);
+    anchor.click();
+    web_sys::Url::revoke_object_url(&url).map_err(error)
+}
+
+/// Names and contents of the text files dropped on the window this frame.
+#[cfg(feature = "serde")]
+pub(crate) fn dropped(ctx: &egui::Context) -> Vec<(String, Result<String, String>)> {
+    ctx.input(|i| i.raw.dropped_files.clone())
+        .into_iter()
+        .map(|file| {
+            let name: String = file
+                .path
+                .as_ref()
+                .and_then(|path| path.file_name())
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or(file.name);
+            // the browser hands over the bytes, native only the path.
+            let contents: Result<Vec<u8>, String> = match (file.bytes, file.path) {
+                (Some(bytes), _) => Ok(bytes.to_vec()),
+                (None, Some(path)) => std::fs::read(path).map_err(|error| error.to_string()),
+                (None, None) => Err("no contents".to_string()),
+            };
+            let contents: Result<String, String> = contents.and_then(|contents| {
+                String::from_utf8(contents).map_err(|error| error.to_string())
+            });
+            (name, contents)
+        })
+        .collect()
+}
@@ -1 +1,10 @@
-pub(crate) mod control_panel;
\ No newline at end of file
+pub(crate) mod calibration;
+pub(crate) mod control_panel;
+pub(crate) mod diagram;
+pub(crate) mod gilrs_source;
+pub(crate) mod keyboard;
+pub(crate) mod mapping;
+pub(crate) mod scripted;
+pub(crate) mod touch;
+pub(crate) mod virtual_device;
+pub(crate) mod widgets;
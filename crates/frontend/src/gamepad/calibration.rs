@@ -0,0 +1,422 @@
+use std::collections::BTreeMap;
+
+use egui::DragValue;
+use gilrs::Axis;
+
+use crate::input::binding::ControllerModel;
+use crate::input::event::{InputEvent, InputEventKind};
+
+/// Measured travel of an axis, raw values.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+struct AxisRange {
+    min: f32,
+    center: f32,
+    max: f32,
+}
+
+impl AxisRange {
+    fn apply(&self, value: f32) -> f32 {
+        let span: f32 = if value >= self.center {
+            self.max - self.center
+        } else {
+            self.center - self.min
+        };
+        if span <= f32::EPSILON {
+            return 0f32;
+        }
+        ((value - self.center) / span).clamp(-1f32, 1f32)
+    }
+}
+
+/// Measured travel of an analog button like a trigger, raw values.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+struct ButtonRange {
+    min: f32,
+    max: f32,
+}
+
+impl ButtonRange {
+    fn apply(&self, value: f32) -> f32 {
+        let span: f32 = self.max - self.min;
+        if span <= f32::EPSILON {
+            return value;
+        }
+        ((value - self.min) / span).clamp(0f32, 1f32)
+    }
+}
+
+/// Ranges of one controller model, keyed by raw event code.
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+struct Calibration {
+    axes: BTreeMap<u32, AxisRange>,
+    buttons: BTreeMap<u32, ButtonRange>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+struct ModelCalibration {
+    model: ControllerModel,
+    calibration: Calibration,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Step {
+    // sticks and triggers released.
+    Center,
+    // sticks circled and triggers pulled all the way.
+    Range,
+}
+
+struct Wizard {
+    model: ControllerModel,
+    step: Step,
+    // latest raw value per axis code while centered.
+    centers: BTreeMap<u32, f32>,
+    // raw extremes per code.
+    axes: BTreeMap<u32, (f32, f32)>,
+    buttons: BTreeMap<u32, (f32, f32)>,
+}
+
+impl Wizard {
+    fn record(&mut self, event: &InputEvent) {
+        match (self.step, event.kind) {
+            (Step::Center, InputEventKind::AxisChanged { code, value, .. }) => {
+                self.centers.insert(code, value);
+            }
+            (Step::Range, InputEventKind::AxisChanged { code, value, .. }) => {
+                let (min, max) = self.axes.entry(code).or_insert((value, value));
+                *min = min.min(value);
+                *max = max.max(value);
+            }
+            (Step::Range, InputEventKind::ButtonChanged { code, value, .. }) => {
+                let (min, max) = self.buttons.entry(code).or_insert((value, value));
+                *min = min.min(value);
+                *max = max.max(value);
+            }
+            _ => (),
+        }
+    }
+
+    fn finish(&self) -> Calibration {
+        let axes: BTreeMap<u32, AxisRange> = self
+            .axes
+            .iter()
+            .map(|(code, (min, max))| {
+                // axes that never moved while centered rest at zero.
+                let center: f32 = self.centers.get(code).copied().unwrap_or_default();
+                (
+                    *code,
+                    AxisRange {
+                        min: min.min(center),
+                        center,
+                        max: max.max(center),
+                    },
+                )
+            })
+            .collect();
+        let buttons: BTreeMap<u32, ButtonRange> = self
+            .buttons
+            .iter()
+            // digital buttons only ever report 0 and 1.
+            .filter(|(_, (min, max))| max - min > f32::EPSILON)
+            .map(|(code, (min, max))| {
+                (
+                    *code,
+                    ButtonRange {
+                        min: *min,
+                        max: *max,
+                    },
+                )
+            })
+            .collect();
+        Calibration { axes, buttons }
+    }
+}
+
+/// Stick and trigger calibrations per controller model, applied before the deadzone.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub(crate) struct Calibrations {
+    models: Vec<ModelCalibration>,
+    // stick deflection below which the stick reads zero.
+    deadzone: f32,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    wizard: Option<Wizard>,
+}
+
+impl Default for Calibrations {
+    fn default() -> Self {
+        Self {
+            models: Vec::new(),
+            deadzone: 0.1,
+            wizard: None,
+        }
+    }
+}
+
+impl Calibrations {
+    fn get(&self, model: ControllerModel) -> Option<&Calibration> {
+        self.models
+            .iter()
+            .find(|m| m.model == model)
+            .map(|m| &m.calibration)
+    }
+
+    /// Whether events of this model are being recorded and must not reach the robot.
+    pub(crate) fn recording(&self, model: Option<ControllerModel>) -> bool {
+        self.wizard
+            .as_ref()
+            .is_some_and(|wizard| Some(wizard.model) == model)
+    }
+
+    pub(crate) fn record(&mut self, event: &InputEvent) {
+        if let Some(wizard) = &mut self.wizard {
+            wizard.record(event);
+        }
+    }
+
+    /// Maps raw values onto the calibrated range, then applies the deadzone to the sticks.
+    pub(crate) fn apply(
+        &self,
+        model: Option<ControllerModel>,
+        mut event: InputEvent,
+    ) -> InputEvent {
+        let calibration: Option<&Calibration> = model.and_then(|model| self.get(model));
+        match &mut event.kind {
+            InputEventKind::AxisChanged { axis, code, value } => {
+                if let Some(range) = calibration.and_then(|c| c.axes.get(code)) {
+                    *value = range.apply(*value);
+                }
+                if matches!(
+                    axis,
+                    Axis::LeftStickX | Axis::LeftStickY | Axis::RightStickX | Axis::RightStickY
+                ) {
+                    *value = deadzone(*value, self.deadzone);
+                }
+            }
+            InputEventKind::ButtonChanged { code, value, .. } => {
+                if let Some(range) = calibration.and_then(|c| c.buttons.get(code)) {
+                    *value = range.apply(*value);
+                }
+            }
+            _ => (),
+        }
+        event
+    }
+
+    /// `rest` holds the current raw axis values, the starting point for the centers.
+    pub(crate) fn ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        model: Option<ControllerModel>,
+        rest: BTreeMap<u32, f32>,
+    ) {
+        ui.horizontal(|ui| {
+            ui.label("Deadzone");
+            ui.add(
+                DragValue::new(&mut self.deadzone)
+                    .speed(0.005)
+                    .clamp_range(0.0..=0.5),
+            );
+        });
+
+        let Some(model) = model else {
+            ui.label("The controller reports no vendor and product ID.");
+            return;
+        };
+
+        let mut finished: Option<Calibration> = None;
+        let mut cancelled: bool = false;
+        match &mut self.wizard {
+            Some(wizard) if wizard.model == model => {
+                match wizard.step {
+                    Step::Center => {
+                        ui.label("1/2 Let go of both sticks and all triggers, then continue.");
+                        ui.label(format!("{} axes at rest", wizard.centers.len()));
+                    }
+                    Step::Range => {
+                        ui.label(
+                            "2/2 Circle both sticks along their edges and pull the triggers all the way.",
+                        );
+                        egui::Grid::new("calibration_ranges_grid")
+                            .striped(true)
+                            .num_columns(3)
+                            .show(ui, |ui| {
+                                for (code, (min, max)) in &wizard.axes {
+                                    ui.monospace(code.to_string());
+                                    ui.monospace(format!("{min:+.3}"));
+                                    ui.monospace(format!("{max:+.3}"));
+                                    ui.end_row();
+                                }
+                                for (code, (min, max)) in &wizard.buttons {
+                                    ui.monospace(code.to_string());
+                                    ui.monospace(format!("{min:.3}"));
+                                    ui.monospace(format!("{max:.3}"));
+                                    ui.end_row();
+                                }
+                            });
+                    }
+                }
+                ui.horizontal(|ui| {
+                    match wizard.step {
+                        Step::Center => {
+                            if ui.button("Next ▶").clicked() {
+                                wizard.step = Step::Range;
+                            }
+                        }
+                        Step::Range => {
+                            if ui.button("✔ Finish").clicked() {
+                                finished = Some(wizard.finish());
+                            }
+                        }
+                    }
+                    cancelled = ui.button("Cancel").clicked();
+                });
+            }
+            _ => {
+                let calibrated: bool = self.get(model).is_some();
+                ui.label(if calibrated {
+                    format!("{model} is calibrated.")
+                } else {
+                    format!("{model} is not calibrated.")
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("📐 Calibrate").clicked() {
+                        self.wizard = Some(Wizard {
+                            model,
+                            step: Step::Center,
+                            centers: rest,
+                            axes: BTreeMap::new(),
+                            buttons: BTreeMap::new(),
+                        });
+                    }
+                    if ui
+                        .add_enabled(calibrated, egui::Button::new("🗑 Clear"))
+                        .clicked()
+                    {
+                        self.models.retain(|m| m.model != model);
+                    }
+                });
+            }
+        }
+
+        if let Some(calibration) = finished {
+            self.models.retain(|m| m.model != model);
+            self.models.push(ModelCalibration { model, calibration });
+            self.wizard = None;
+        }
+        if cancelled {
+            self.wizard = None;
+        }
+    }
+}
+
+fn deadzone(value: f32, threshold: f32) -> f32 {
+    if value.abs() <= threshold {
+        0f32
+    } else {
+        value.signum() * (value.abs() - threshold) / (1f32 - threshold)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::time::SystemTime;
+
+    use gilrs::Button;
+
+    use super::*;
+
+    fn axis(value: f32) -> InputEvent {
+        InputEvent {
+            time: SystemTime::UNIX_EPOCH,
+            kind: InputEventKind::AxisChanged {
+                axis: Axis::LeftStickX,
+                code: 1,
+                value,
+            },
+        }
+    }
+
+    fn trigger(value: f32) -> InputEvent {
+        InputEvent {
+            time: SystemTime::UNIX_EPOCH,
+            kind: InputEventKind::ButtonChanged {
+                button: Button::RightTrigger2,
+                code: 2,
+                value,
+            },
+        }
+    }
+
+    #[test]
+    fn axis_range_scales_each_side_of_the_center() {
+        let range: AxisRange = AxisRange {
+            min: -0.5,
+            center: 0.25,
+            max: 0.75,
+        };
+        assert_eq!(range.apply(0.25), 0.0);
+        assert_eq!(range.apply(0.5), 0.5);
+        assert_eq!(range.apply(-0.125), -0.5);
+        assert_eq!(range.apply(1.0), 1.0);
+        // a range that never moved is ignored.
+        let flat: AxisRange = AxisRange {
+            min: 0.1,
+            center: 0.1,
+            max: 0.1,
+        };
+        assert_eq!(flat.apply(0.5), 0.0);
+    }
+
+    #[test]
+    fn button_range_stretches_the_measured_travel() {
+        let range: ButtonRange = ButtonRange {
+            min: 0.25,
+            max: 0.75,
+        };
+        assert_eq!(range.apply(0.25), 0.0);
+        assert_eq!(range.apply(0.5), 0.5);
+        assert_eq!(range.apply(0.95), 1.0);
+    }
+
+    #[test]
+    fn wizard_measures_centers_and_extremes() {
+        let mut wizard: Wizard = Wizard {
+            model: ControllerModel {
+                vendor: 1,
+                product: 2,
+            },
+            step: Step::Center,
+            centers: BTreeMap::new(),
+            axes: BTreeMap::new(),
+            buttons: BTreeMap::new(),
+        };
+        wizard.record(&axis(0.1));
+        wizard.step = Step::Range;
+        for value in [0.4, -0.6, 0.8] {
+            wizard.record(&axis(value));
+        }
+        for value in [0.05, 0.9] {
+            wizard.record(&trigger(value));
+        }
+
+        let calibration: Calibration = wizard.finish();
+        let range: &AxisRange = &calibration.axes[&1];
+        assert_eq!((range.min, range.center, range.max), (-0.6, 0.1, 0.8));
+        let range: &ButtonRange = &calibration.buttons[&2];
+        assert_eq!((range.min, range.max), (0.05, 0.9));
+    }
+
+    #[test]
+    fn deadzone_rescales_outside_the_threshold() {
+        assert_eq!(deadzone(0.125, 0.25), 0.0);
+        assert_eq!(deadzone(-0.25, 0.25), 0.0);
+        assert_eq!(deadzone(0.625, 0.25), 0.5);
+        assert_eq!(deadzone(-1.0, 0.25), -1.0);
+    }
+}
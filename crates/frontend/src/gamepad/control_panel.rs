@@ -1,4 +1,5 @@
-use std::time::UNIX_EPOCH;
+use std::collections::BTreeMap;
+use std::time::SystemTime;
 
 use eframe::emath::Vec2;
 use egui::RichText;
@@ -6,50 +7,243 @@ use egui_plot::{MarkerShape, PlotPoints, Points};
 use gilrs::{Axis, ev::{
     AxisOrBtn,
     state::GamepadState,
-}, Gamepad, GamepadId, Gilrs, GilrsBuilder};
+}, Gamepad};
 
+use crate::command::control_loop::Shared;
 use crate::command::joints::JointState;
-
+use crate::event_log::{EventLog, LogKind, Severity};
+use crate::gamepad::calibration::Calibrations;
+use crate::gamepad::diagram::{controller_diagram, DiagramInput, DiagramStyle};
+use crate::gamepad::gilrs_source::{controller_model, GilrsSource};
+use crate::gamepad::keyboard::VirtualGamepad;
+use crate::gamepad::mapping::SdlMappings;
+use crate::gamepad::scripted::ScriptedSource;
+use crate::gamepad::touch::TouchGamepad;
+use crate::input::binding::{Bindings, ControllerModel};
+use crate::input::event::InputEventKind;
+use crate::input::role::Role;
+use crate::input::source::{Device, InputSource, SourceEvent};
+
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub(crate) struct GamepadControlPanel {
-    gilrs: Gilrs,
-    current_gamepad: Option<GamepadId>,
-    log_messages: [Option<String>; 300],
+    // `None` when gilrs failed to start, virtual devices keep working.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    gilrs: Option<GilrsSource>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    backend_error: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    current_gamepad: Option<Device>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    keyboard: VirtualGamepad,
+    touch: TouchGamepad,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    scripted: ScriptedSource,
+    // handed in next to the built-in ones, like a test's.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    sources: Vec<Box<dyn InputSource>>,
+    calibrations: Calibrations,
+    mappings: SdlMappings,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    roles: RoleAssignment,
+    // roles that lost their controller, their input is cleared on the next update.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    released: Vec<Role>,
+}
+
+/// Controllers holding each role, events of any other controller don't reach the robot.
+#[derive(Default)]
+struct RoleAssignment {
+    driver: Option<Device>,
+    operator: Option<Device>,
+}
+
+impl RoleAssignment {
+    fn get(&self, role: Role) -> Option<Device> {
+        match role {
+            Role::Driver => self.driver,
+            Role::Operator => self.operator,
+        }
+    }
+
+    fn set(&mut self, role: Role, device: Option<Device>) {
+        match role {
+            Role::Driver => self.driver = device,
+            Role::Operator => self.operator = device,
+        }
+    }
+
+    fn roles(&self, device: Device) -> Vec<Role> {
+        Role::ALL
+            .into_iter()
+            .filter(|role| match (device, role) {
+                (Device::Replayed { driver, .. }, Role::Driver) => driver,
+                (Device::Replayed { operator, .. }, Role::Operator) => operator,
+                _ => self.get(*role) == Some(device),
+            })
+            .collect()
+    }
 }
 
 impl Default for GamepadControlPanel {
     fn default() -> Self {
-        const INIT: Option<String> = None;
-        let gilrs: Gilrs = GilrsBuilder::new().set_update_state(false).build().unwrap();
+        let (gilrs, backend_error) = match GilrsSource::new() {
+            Ok(source) => (Some(source), None),
+            Err(error) => (None, Some(error)),
+        };
         Self {
             gilrs,
-            current_gamepad: None,
-            log_messages: [INIT; 300],
+            backend_error,
+            ..Self::new(Vec::new())
         }
     }
 }
 
 impl GamepadControlPanel {
-    pub(crate) fn update(&mut self, joint_state: &mut JointState) {
-        while let Some(event) = self.gilrs.next_event() {
-            joint_state.update(event.event);
-            self.log(format!(
-                "{} : {} : {:?}",
-                event
-                    .time
-                    .duration_since(UNIX_EPOCH)
-                    .unwrap_or_default()
-                    .as_millis(),
-                event.id,
-                event.event
-            ));
-            self.gilrs.update(&event);
-            if self.current_gamepad.is_none() {
-                self.current_gamepad = Some(event.id);
+    /// Panel without gilrs, polling `sources` after the on-screen gamepads.
+    pub(crate) fn new(sources: Vec<Box<dyn InputSource>>) -> Self {
+        Self {
+            gilrs: None,
+            backend_error: None,
+            current_gamepad: None,
+            keyboard: VirtualGamepad::default(),
+            touch: TouchGamepad::default(),
+            scripted: ScriptedSource::default(),
+            sources,
+            calibrations: Calibrations::default(),
+            mappings: SdlMappings::default(),
+            roles: RoleAssignment::default(),
+            released: Vec::new(),
+        }
+    }
+
+    fn sources(&mut self) -> Vec<&mut dyn InputSource> {
+        let mut sources: Vec<&mut dyn InputSource> =
+            vec![&mut self.keyboard, &mut self.touch, &mut self.scripted];
+        if let Some(gilrs) = &mut self.gilrs {
+            sources.insert(0, gilrs);
+        }
+        for source in &mut self.sources {
+            sources.push(source.as_mut());
+        }
+        sources
+    }
+
+    /// Feeds the events of the controllers holding a role, returns whether any event was received.
+    pub(crate) fn update(
+        &mut self,
+        joint_state: &mut JointState,
+        log: &mut Shared<EventLog>,
+        now: SystemTime,
+    ) -> bool {
+        if let Some(source) = &mut self.gilrs {
+            self.mappings.start(source.gilrs_mut());
+        }
+        for role in self.released.drain(..) {
+            joint_state.release(role);
+        }
+
+        let mut events: Vec<SourceEvent> = self
+            .sources()
+            .into_iter()
+            .flat_map(|source| source.poll(now))
+            .collect();
+        // a replay plays after the live sources, they can only e-stop meanwhile.
+        if let Some(replay) = joint_state.recorder.source() {
+            events.extend(replay.poll(now));
+        }
+        let received: bool = !events.is_empty();
+        let names: Vec<(Device, String)> = if received {
+            let mut names: Vec<(Device, String)> = self
+                .sources()
+                .into_iter()
+                .flat_map(|source| source.devices())
+                .collect();
+            if let Some(replay) = joint_state.recorder.source() {
+                names.extend(replay.devices());
+            }
+            names
+        } else {
+            Vec::new()
+        };
+        for SourceEvent {
+            device,
+            model,
+            event,
+        } in events
+        {
+            match (device, event.kind) {
+                (Device::Gamepad(id), InputEventKind::Connected) => {
+                    if let Some(source) = &mut self.gilrs {
+                        self.mappings.connected(source.gilrs_mut(), id);
+                    }
+                }
+                (_, InputEventKind::Disconnected) => {
+                    // the robot must not keep the last stick positions of a pulled cable.
+                    for role in self.roles.roles(device) {
+                        joint_state.disconnected(role);
+                    }
+                    if self.current_gamepad == Some(device) {
+                        self.current_gamepad = None;
+                    }
+                }
+                _ => (),
+            }
+
+            let roles: Vec<Role> = self.roles.roles(device);
+            let mapping: bool = matches!(device, Device::Gamepad(id) if self.mappings.recording(id));
+            if let Device::Replayed { .. } = device {
+                // recorded after calibration, it plays as it was.
+                joint_state.replayed(&roles, model, &event);
+            } else if mapping {
+                self.mappings.record(&event.kind);
+            } else if self.calibrations.recording(model) {
+                // sticks are swept while calibrating, the robot must not follow.
+                self.calibrations.record(&event);
+            } else if !roles.is_empty() {
+                joint_state.update(&roles, model, &self.calibrations.apply(model, event));
+            }
+
+            let name: String = names
+                .iter()
+                .find(|(d, _)| *d == device)
+                .map(|(_, name)| name.clone())
+                .unwrap_or_else(|| format!("{device:?}"));
+            let (severity, kind): (Severity, LogKind) = match event.kind {
+                InputEventKind::Connected => (Severity::Info, LogKind::Connection),
+                InputEventKind::Disconnected => (Severity::Warning, LogKind::Connection),
+                _ => (Severity::Debug, LogKind::Gamepad),
+            };
+            log.push(event.time, name, severity, kind, format!("{:?}", event.kind));
+            let selectable: bool = !matches!(device, Device::Replayed { .. })
+                && event.kind != InputEventKind::Disconnected;
+            if self.current_gamepad.is_none() && selectable {
+                self.current_gamepad = Some(device);
             }
         }
+
+        received
+    }
+
+    /// Whether a connected controller holds the role, so it can be re-acquired.
+    pub(crate) fn holds(&self, role: Role) -> bool {
+        match self.roles.get(role) {
+            Some(Device::Gamepad(id)) => self
+                .gilrs
+                .as_ref()
+                .is_some_and(|source| source.gilrs().connected_gamepad(id).is_some()),
+            Some(Device::Keyboard | Device::Touch | Device::Scripted) => true,
+            Some(Device::Replayed { .. }) | None => false,
+        }
     }
 
-    pub(crate) fn ui(&mut self, ui: &mut egui::Ui) {
+    /// Reads the keyboard and touch screen for the virtual gamepads, called at the start of every frame.
+    pub(crate) fn frame(&mut self, ctx: &egui::Context) {
+        self.keyboard.frame(ctx);
+        self.touch.frame(ctx);
+    }
+
+    pub(crate) fn ui(&mut self, ui: &mut egui::Ui, bindings: &Bindings) {
         egui::ScrollArea::vertical()
             .max_height(ui.available_height())
             .show(ui, |ui| {
@@ -57,21 +251,12 @@ impl GamepadControlPanel {
 
                 ui.separator();
 
-                self.current_gamepad_details_ui(ui);
-
-                ui.separator();
-
-                gamepad_logs(ui, &self.log_messages);
+                self.current_gamepad_details_ui(ui, bindings);
 
                 ui.allocate_space(ui.available_size());
             });
     }
 
-    fn log(&mut self, message: String) {
-        self.log_messages[0..].rotate_right(1);
-        self.log_messages[0] = Some(message);
-    }
-
     fn gamepad_list_ui(&mut self, ui: &mut egui::Ui) {
         ui.vertical_centered(|ui| {
             ui.heading("📃 List");
@@ -83,25 +268,71 @@ impl GamepadControlPanel {
             .max_height(150f32)
             .id_source("gamepad_list_ui")
             .show(ui, |ui| {
-                for (id, gamepad) in self.gilrs.gamepads() {
-                    if ui
-                        .selectable_label(
-                            self.current_gamepad == Some(id),
-                            format!("{id}: {}", gamepad.name()),
-                        )
-                        .clicked()
-                    {
-                        self.current_gamepad = Some(id);
-                    };
+                let devices: Vec<(Device, String)> = self
+                    .sources()
+                    .into_iter()
+                    .flat_map(|source| source.devices())
+                    .collect();
+                for (device, name) in devices {
+                    ui.horizontal(|ui| {
+                        for role in Role::ALL {
+                            let assigned: bool = self.roles.get(role) == Some(device);
+                            if ui.selectable_label(assigned, role.to_string()).clicked() {
+                                self.roles.set(role, (!assigned).then_some(device));
+                                self.released.push(role);
+                            }
+                        }
+                        if ui
+                            .selectable_label(self.current_gamepad == Some(device), name)
+                            .clicked()
+                        {
+                            self.current_gamepad = Some(device);
+                        };
+                    });
                 }
             });
+
+        if let Some(error) = &self.backend_error {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                format!("⚠ No controller backend, only virtual devices are available: {error}"),
+            );
+        }
+
+        if self.roles.driver.is_none() && self.roles.operator.is_none() {
+            ui.label("Assign a role to a controller to command the robot, unassigned controllers are ignored.");
+        }
     }
 
-    fn current_gamepad_details_ui(&mut self, ui: &mut egui::Ui) {
-        if let Some(gamepad_id) = self.current_gamepad {
-            let gamepad: Gamepad = self.gilrs.gamepad(gamepad_id);
+    fn current_gamepad_details_ui(&mut self, ui: &mut egui::Ui, bindings: &Bindings) {
+        let current: Option<Device> = self.current_gamepad;
+        if let (Some(Device::Gamepad(gamepad_id)), Some(source)) = (current, &mut self.gilrs) {
+            let gamepad: Gamepad = source.gilrs().gamepad(gamepad_id);
             let gamepad_state: &GamepadState = gamepad.state();
 
+            let model: Option<ControllerModel> = controller_model(&gamepad);
+            ui.vertical_centered(|ui| {
+                ui.heading("🎮 Controller");
+            });
+
+            ui.separator();
+
+            controller_diagram(
+                ui,
+                DiagramStyle::detect(model),
+                &DiagramInput {
+                    button: &|button| {
+                        gamepad.button_data(button).map_or(0f32, |data| {
+                            data.value().max(f32::from(u8::from(data.is_pressed())))
+                        })
+                    },
+                    axis: &|axis| gamepad.value(axis),
+                    actions: &|input| bindings.bound(model, input),
+                },
+            );
+
+            ui.separator();
+
             gamepad_sticks_plotter(ui, &gamepad, gamepad_state);
 
             ui.separator();
@@ -111,6 +342,52 @@ impl GamepadControlPanel {
             ui.separator();
 
             gamepad_info(ui, &gamepad);
+
+            ui.separator();
+
+            ui.vertical_centered(|ui| {
+                ui.heading("📐 Calibration");
+            });
+
+            ui.separator();
+
+            let rest: BTreeMap<u32, f32> = gamepad_state
+                .axes()
+                .map(|(code, axis_data)| (code.into_u32(), axis_data.value()))
+                .collect();
+            let calibrating: bool = self.calibrations.recording(model);
+            self.calibrations.ui(ui, model, rest);
+            if !calibrating && self.calibrations.recording(model) {
+                // the wizard swallows the events of every controller of the model.
+                for role in Role::ALL {
+                    if let Some(Device::Gamepad(id)) = self.roles.get(role) {
+                        if controller_model(&source.gilrs().gamepad(id)) == model {
+                            self.released.push(role);
+                        }
+                    }
+                }
+            }
+
+            ui.separator();
+
+            ui.vertical_centered(|ui| {
+                ui.heading("🗺 Mapping");
+            });
+
+            ui.separator();
+
+            let mapping: bool = self.mappings.recording(gamepad_id);
+            self.mappings.ui(ui, source.gilrs_mut(), gamepad_id);
+            if !mapping && self.mappings.recording(gamepad_id) {
+                // the editor swallows the events, whatever is held would keep driving the robot.
+                self.released.extend(self.roles.roles(Device::Gamepad(gamepad_id)));
+            }
+        } else if let Some(Device::Keyboard) = self.current_gamepad {
+            self.keyboard.ui(ui);
+        } else if let Some(Device::Touch) = self.current_gamepad {
+            self.touch.ui(ui);
+        } else if let Some(Device::Scripted) = self.current_gamepad {
+            self.scripted.ui(ui);
         } else {
             ui.label("Press a button on a controller or select it from the left.");
         }
@@ -262,19 +539,85 @@ fn gamepad_info(ui: &mut egui::Ui, gamepad: &Gamepad) {
         });
 }
 
-fn gamepad_logs(ui: &mut egui::Ui, messages: &[Option<String>; 300]) {
-    ui.vertical_centered(|ui| {
-        ui.heading("📝 Logs");
-    });
-
-    ui.separator();
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::command::recording::RecorderRequest;
+    use crate::gamepad::scripted::ScriptStep;
+
+    #[test]
+    fn scripted_events_reach_the_drive_output() {
+        let mut script: ScriptedSource = ScriptedSource::new(vec![
+            ScriptStep::axis(0.0, Axis::LeftStickY, 1.0),
+            ScriptStep::axis(1.0, Axis::LeftStickY, 0.0),
+        ]);
+        script.play();
+        let mut panel: GamepadControlPanel = GamepadControlPanel::new(vec![Box::new(script)]);
+        panel.roles.set(Role::Driver, Some(Device::Scripted));
+        let mut joints: JointState = JointState::default();
+        let mut log: Shared<EventLog> = Shared::default();
+        let mut step = |seconds: u64| -> (bool, (f64, f64)) {
+            let now: SystemTime = SystemTime::UNIX_EPOCH + Duration::from_secs(seconds);
+            let received: bool = panel.update(&mut joints, &mut log, now);
+            joints.tick(now);
+            (received, joints.drive_output())
+        };
+
+        let (received, forward): (bool, (f64, f64)) = step(0);
+        assert!(received && forward.0 > 0f64 && forward.0 == forward.1);
+        // nothing new until the next step is due, the stick stays held.
+        assert_eq!(step(0), (false, forward));
+        assert_eq!(step(1), (true, (0f64, 0f64)));
+    }
 
-    egui::ScrollArea::vertical()
-        .max_height(ui.available_height())
-        .show(ui, |ui| {
-            for message in messages.iter().flatten() {
-                ui.label(message);
-            }
-            ui.allocate_space(ui.available_size());
-        });
+    #[test]
+    fn replays_end_without_anything_held() {
+        fn at(seconds: u64) -> SystemTime {
+            SystemTime::UNIX_EPOCH + Duration::from_secs(seconds)
+        }
+        let mut script: ScriptedSource =
+            ScriptedSource::new(vec![ScriptStep::axis(0.0, Axis::LeftStickY, 1.0)]);
+        script.play();
+        let mut panel: GamepadControlPanel = GamepadControlPanel::new(vec![Box::new(script)]);
+        panel.roles.set(Role::Driver, Some(Device::Scripted));
+        let mut joints: JointState = JointState::default();
+        let mut log: Shared<EventLog> = Shared::default();
+
+        #[cfg(feature = "serde")]
+        let settings: String = ron::to_string(&joints).expect("serializable settings");
+        #[cfg(not(feature = "serde"))]
+        let settings: String = String::new();
+        joints
+            .recorder
+            .start(settings, false, false, BTreeSet::new(), at(0));
+        for seconds in [0, 2] {
+            panel.update(&mut joints, &mut log, at(seconds));
+            joints.tick(at(seconds));
+        }
+        joints.recorder.stop();
+        panel.roles.set(Role::Driver, None);
+
+        // stopped while the stick is held.
+        joints.request(RecorderRequest::Replay);
+        panel.update(&mut joints, &mut log, at(10));
+        joints.tick(at(10));
+        assert!(joints.drive_output().0 > 0f64);
+        joints.request(RecorderRequest::Stop);
+        assert_eq!(joints.drive_output(), (0f64, 0f64));
+
+        // played to the end next to a live lost controller.
+        joints.disconnected(Role::Operator);
+        joints.request(RecorderRequest::Replay);
+        for seconds in [20, 22] {
+            panel.update(&mut joints, &mut log, at(seconds));
+            joints.tick(at(seconds));
+            assert_eq!(joints.lost, BTreeSet::from([Role::Operator]));
+        }
+        assert!(!joints.recorder.replaying());
+        joints.reacquire(Role::Operator);
+        assert_eq!(joints.drive_output(), (0f64, 0f64));
+    }
 }
@@ -0,0 +1,271 @@
+use egui::{Align2, Color32, FontId, Pos2, Rect, Rounding, Sense, Stroke, Vec2};
+use gilrs::{Axis, Button};
+
+use crate::input::action::Action;
+use crate::input::binding::{ControllerModel, Input};
+
+// vendor id of Sony controllers.
+const SONY: u16 = 0x054c;
+
+/// Drawn layout, only the stick and d-pad positions and the face button glyphs differ.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum DiagramStyle {
+    Xbox,
+    PlayStation,
+}
+
+impl DiagramStyle {
+    pub(crate) fn detect(model: Option<ControllerModel>) -> Self {
+        match model {
+            Some(model) if model.vendor == SONY => DiagramStyle::PlayStation,
+            _ => DiagramStyle::Xbox,
+        }
+    }
+
+    fn face(&self, button: Button) -> (&'static str, Color32) {
+        match (self, button) {
+            (DiagramStyle::Xbox, Button::South) => ("A", Color32::from_rgb(96, 176, 64)),
+            (DiagramStyle::Xbox, Button::East) => ("B", Color32::from_rgb(208, 64, 56)),
+            (DiagramStyle::Xbox, Button::West) => ("X", Color32::from_rgb(56, 120, 208)),
+            (DiagramStyle::Xbox, Button::North) => ("Y", Color32::from_rgb(224, 184, 48)),
+            (DiagramStyle::PlayStation, Button::South) => ("✕", Color32::from_rgb(120, 152, 216)),
+            (DiagramStyle::PlayStation, Button::East) => ("○", Color32::from_rgb(216, 96, 104)),
+            (DiagramStyle::PlayStation, Button::West) => ("□", Color32::from_rgb(208, 128, 184)),
+            (DiagramStyle::PlayStation, Button::North) => ("△", Color32::from_rgb(64, 184, 160)),
+            _ => ("", Color32::GRAY),
+        }
+    }
+
+    // centers of the left stick, d-pad, right stick and face buttons, as fractions of the body.
+    fn positions(&self) -> [Pos2; 4] {
+        let upper_left: Pos2 = Pos2::new(0.25, 0.4);
+        let lower_left: Pos2 = Pos2::new(0.37, 0.66);
+        let lower_right: Pos2 = Pos2::new(0.63, 0.66);
+        let upper_right: Pos2 = Pos2::new(0.75, 0.4);
+        match self {
+            DiagramStyle::Xbox => [upper_left, lower_left, lower_right, upper_right],
+            DiagramStyle::PlayStation => [lower_left, upper_left, lower_right, upper_right],
+        }
+    }
+}
+
+/// Live state of the drawn controller.
+pub(crate) struct DiagramInput<'a> {
+    pub(crate) button: &'a dyn Fn(Button) -> f32,
+    pub(crate) axis: &'a dyn Fn(Axis) -> f32,
+    // actions bound to an input, written next to its control.
+    pub(crate) actions: &'a dyn Fn(Input) -> Vec<Action>,
+}
+
+// a control and the text of its callout.
+struct Callout {
+    anchor: Pos2,
+    text: String,
+}
+
+pub(crate) fn controller_diagram(ui: &mut egui::Ui, style: DiagramStyle, state: &DiagramInput) {
+    let width: f32 = ui.available_width().clamp(320f32, 640f32);
+    let (rect, _) = ui.allocate_exact_size(Vec2::new(width, width * 0.5), Sense::hover());
+    // the side columns hold the callouts.
+    let body: Rect = Rect::from_center_size(rect.center(), Vec2::new(width * 0.5, width * 0.42));
+    let at = |fraction: Pos2| -> Pos2 { body.min + fraction.to_vec2() * body.size() };
+    let unit: f32 = body.width();
+
+    let painter: egui::Painter = ui.painter_at(rect);
+    let visuals: &egui::Visuals = ui.visuals();
+    let stroke: Stroke = visuals.widgets.inactive.fg_stroke;
+    let fill: Color32 = visuals.widgets.inactive.bg_fill;
+    let active: Color32 = visuals.selection.bg_fill;
+    let text: Color32 = visuals.text_color();
+    let font: FontId = FontId::proportional(unit * 0.045);
+
+    let labels = |inputs: &[(&str, Input)]| -> String {
+        inputs
+            .iter()
+            .filter_map(|(prefix, input)| {
+                let actions: Vec<String> = (state.actions)(*input)
+                    .iter()
+                    .map(|action| action.to_string())
+                    .collect();
+                (!actions.is_empty()).then(|| format!("{prefix}{}", actions.join(", ")))
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    };
+    let mut callouts: Vec<Callout> = Vec::new();
+    let mut callout = |anchor: Pos2, inputs: &[(&str, Input)]| {
+        let text: String = labels(inputs);
+        if !text.is_empty() {
+            callouts.push(Callout { anchor, text });
+        }
+    };
+
+    // body and grips.
+    for grip in [Pos2::new(0.2, 0.78), Pos2::new(0.8, 0.78)] {
+        painter.circle(at(grip), unit * 0.17, fill, stroke);
+    }
+    painter.rect(
+        Rect::from_min_max(at(Pos2::new(0.0, 0.2)), at(Pos2::new(1.0, 0.8))),
+        Rounding::same(unit * 0.12),
+        fill,
+        stroke,
+    );
+
+    // triggers fill up with their depth, bumpers light up when pressed.
+    for (trigger, bumper, x) in [
+        (Button::LeftTrigger2, Button::LeftTrigger, 0.2),
+        (Button::RightTrigger2, Button::RightTrigger, 0.8),
+    ] {
+        let trigger_rect: Rect =
+            Rect::from_center_size(at(Pos2::new(x, 0.04)), Vec2::new(unit * 0.12, unit * 0.07));
+        let depth: f32 = (state.button)(trigger).clamp(0f32, 1f32);
+        painter.rect(trigger_rect, Rounding::same(unit * 0.02), fill, stroke);
+        painter.rect_filled(
+            Rect::from_min_max(
+                Pos2::new(
+                    trigger_rect.min.x,
+                    trigger_rect.max.y - trigger_rect.height() * depth,
+                ),
+                trigger_rect.max,
+            ),
+            Rounding::same(unit * 0.02),
+            active,
+        );
+        callout(trigger_rect.center(), &[("", Input::Button(trigger))]);
+
+        let bumper_rect: Rect =
+            Rect::from_center_size(at(Pos2::new(x, 0.14)), Vec2::new(unit * 0.18, unit * 0.04));
+        let pressed: bool = (state.button)(bumper) >= 0.5;
+        painter.rect(
+            bumper_rect,
+            Rounding::same(unit * 0.02),
+            if pressed { active } else { fill },
+            stroke,
+        );
+        callout(bumper_rect.center(), &[("", Input::Button(bumper))]);
+    }
+
+    let [left_stick, dpad, right_stick, face] = style.positions().map(at);
+
+    // sticks, the knob follows the axes and lights up when clicked.
+    for (center, x, y, thumb) in [
+        (
+            left_stick,
+            Axis::LeftStickX,
+            Axis::LeftStickY,
+            Button::LeftThumb,
+        ),
+        (
+            right_stick,
+            Axis::RightStickX,
+            Axis::RightStickY,
+            Button::RightThumb,
+        ),
+    ] {
+        let radius: f32 = unit * 0.08;
+        painter.circle(center, radius, visuals.extreme_bg_color, stroke);
+        let deflection: Vec2 = Vec2::new((state.axis)(x), -(state.axis)(y));
+        let pressed: bool = (state.button)(thumb) >= 0.5;
+        painter.circle(
+            center + deflection * radius * 0.6,
+            radius * 0.45,
+            if pressed { active } else { fill },
+            stroke,
+        );
+        callout(
+            center,
+            &[
+                ("X: ", Input::Axis(x)),
+                ("Y: ", Input::Axis(y)),
+                ("Click: ", Input::Button(thumb)),
+            ],
+        );
+    }
+
+    // d-pad.
+    let arm: f32 = unit * 0.05;
+    for (button, offset) in [
+        (Button::DPadUp, Vec2::new(0.0, -1.0)),
+        (Button::DPadDown, Vec2::new(0.0, 1.0)),
+        (Button::DPadLeft, Vec2::new(-1.0, 0.0)),
+        (Button::DPadRight, Vec2::new(1.0, 0.0)),
+    ] {
+        let center: Pos2 = dpad + offset * arm;
+        let pressed: bool = (state.button)(button) >= 0.5;
+        painter.rect(
+            Rect::from_center_size(center, Vec2::splat(arm)),
+            Rounding::same(unit * 0.008),
+            if pressed { active } else { fill },
+            stroke,
+        );
+        callout(center, &[("", Input::Button(button))]);
+    }
+
+    // face buttons.
+    let spread: f32 = unit * 0.065;
+    for (button, offset) in [
+        (Button::North, Vec2::new(0.0, -1.0)),
+        (Button::South, Vec2::new(0.0, 1.0)),
+        (Button::West, Vec2::new(-1.0, 0.0)),
+        (Button::East, Vec2::new(1.0, 0.0)),
+    ] {
+        let center: Pos2 = face + offset * spread;
+        let (glyph, color) = style.face(button);
+        let pressed: bool = (state.button)(button) >= 0.5;
+        painter.circle(
+            center,
+            unit * 0.035,
+            if pressed { color } else { fill },
+            Stroke::new(stroke.width * 1.5, color),
+        );
+        painter.text(
+            center,
+            Align2::CENTER_CENTER,
+            glyph,
+            font.clone(),
+            if pressed { Color32::WHITE } else { color },
+        );
+        callout(center, &[("", Input::Button(button))]);
+    }
+
+    // select, mode and start.
+    for (button, position) in [
+        (Button::Select, Pos2::new(0.41, 0.4)),
+        (Button::Mode, Pos2::new(0.5, 0.3)),
+        (Button::Start, Pos2::new(0.59, 0.4)),
+    ] {
+        let center: Pos2 = at(position);
+        let pressed: bool = (state.button)(button) >= 0.5;
+        painter.circle(
+            center,
+            unit * 0.025,
+            if pressed { active } else { fill },
+            stroke,
+        );
+        callout(center, &[("", Input::Button(button))]);
+    }
+
+    // callouts go to the column on their side, stacked top to bottom so they don't overlap.
+    let line_height: f32 = ui.fonts(|fonts| fonts.row_height(&font)) + 2f32;
+    let (mut left, mut right): (Vec<Callout>, Vec<Callout>) = callouts
+        .into_iter()
+        .partition(|callout| callout.anchor.x < body.center().x);
+    for (callouts, x, align) in [
+        (&mut left, body.min.x - unit * 0.05, Align2::RIGHT_TOP),
+        (&mut right, body.max.x + unit * 0.05, Align2::LEFT_TOP),
+    ] {
+        callouts.sort_by(|a, b| a.anchor.y.total_cmp(&b.anchor.y));
+        let mut next: f32 = rect.min.y;
+        for callout in callouts.iter() {
+            let lines: usize = callout.text.lines().count();
+            let top: f32 = (callout.anchor.y - line_height / 2f32).max(next);
+            next = top + line_height * lines as f32;
+            let label: Pos2 = Pos2::new(x, top);
+            painter.line_segment(
+                [callout.anchor, Pos2::new(x, top + line_height / 2f32)],
+                Stroke::new(1f32, visuals.weak_text_color().gamma_multiply(0.5)),
+            );
+            painter.text(label, align, &callout.text, font.clone(), text);
+        }
+    }
+}
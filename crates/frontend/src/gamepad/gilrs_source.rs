@@ -0,0 +1,76 @@
+use std::time::SystemTime;
+
+use gilrs::{
+    ev::filter::{axis_dpad_to_button, Jitter},
+    Filter, Gamepad, Gilrs, GilrsBuilder,
+};
+
+use crate::input::binding::ControllerModel;
+use crate::input::event::InputEvent;
+use crate::input::source::{Device, InputSource, SourceEvent};
+
+/// Physical controllers through gilrs.
+pub(crate) struct GilrsSource {
+    gilrs: Gilrs,
+    jitter: Jitter,
+}
+
+impl GilrsSource {
+    /// Fails on platforms without gamepad support or when the backend can't start.
+    pub(crate) fn new() -> Result<Self, String> {
+        // the deadzone filter is left out, calibration has to see raw values.
+        let gilrs: Gilrs = GilrsBuilder::new()
+            .set_update_state(false)
+            .with_default_filters(false)
+            .build()
+            .map_err(|error| error.to_string())?;
+        Ok(Self {
+            gilrs,
+            jitter: Jitter::new(),
+        })
+    }
+
+    pub(crate) fn gilrs(&self) -> &Gilrs {
+        &self.gilrs
+    }
+
+    pub(crate) fn gilrs_mut(&mut self) -> &mut Gilrs {
+        &mut self.gilrs
+    }
+}
+
+impl InputSource for GilrsSource {
+    fn poll(&mut self, _now: SystemTime) -> Vec<SourceEvent> {
+        let mut events: Vec<SourceEvent> = Vec::new();
+        while let Some(event) = self
+            .gilrs
+            .next_event()
+            .filter_ev(&axis_dpad_to_button, &mut self.gilrs)
+            .filter_ev(&self.jitter, &mut self.gilrs)
+        {
+            self.gilrs.update(&event);
+            if let Some(input) = InputEvent::from_gilrs(&event) {
+                events.push(SourceEvent {
+                    device: Device::Gamepad(event.id),
+                    model: controller_model(&self.gilrs.gamepad(event.id)),
+                    event: input,
+                });
+            }
+        }
+        events
+    }
+
+    fn devices(&self) -> Vec<(Device, String)> {
+        self.gilrs
+            .gamepads()
+            .map(|(id, gamepad)| (Device::Gamepad(id), format!("{id}: {}", gamepad.name())))
+            .collect()
+    }
+}
+
+pub(crate) fn controller_model(gamepad: &Gamepad) -> Option<ControllerModel> {
+    gamepad
+        .vendor_id()
+        .zip(gamepad.product_id())
+        .map(|(vendor, product)| ControllerModel { vendor, product })
+}
@@ -0,0 +1,246 @@
+use std::time::SystemTime;
+
+use egui::{Key, Modifiers, Pos2, Rect, Sense, Stroke, Vec2};
+use gilrs::{Axis, Button};
+
+use crate::gamepad::virtual_device::VirtualDevice;
+use crate::input::source::{Device, InputSource, SourceEvent};
+
+// keys held down press these buttons.
+const BUTTON_KEYS: [(Key, Button); 8] = [
+    (Key::Escape, Button::Start),
+    // egui moves the focus with tab.
+    (Key::C, Button::Select),
+    (Key::E, Button::DPadUp),
+    (Key::Q, Button::DPadDown),
+    (Key::I, Button::North),
+    (Key::K, Button::South),
+    (Key::L, Button::East),
+    (Key::J, Button::West),
+];
+
+// keys pushing an axis to +1 and -1.
+const AXIS_KEYS: [(Axis, [Key; 2], [Key; 2]); 2] = [
+    (
+        Axis::LeftStickY,
+        [Key::W, Key::ArrowUp],
+        [Key::S, Key::ArrowDown],
+    ),
+    (
+        Axis::LeftStickX,
+        [Key::D, Key::ArrowRight],
+        [Key::A, Key::ArrowLeft],
+    ),
+];
+
+/// Gamepad driven by the keyboard and a mouse dragged stick, feeding the same events as a real one.
+pub(crate) struct VirtualGamepad {
+    // mouse stick position, y up.
+    stick: Vec2,
+    // where the mouse stick was drawn last frame, `None` if it wasn't.
+    pad: Option<Rect>,
+    device: VirtualDevice,
+}
+
+impl Default for VirtualGamepad {
+    fn default() -> Self {
+        Self {
+            stick: Vec2::ZERO,
+            pad: None,
+            device: VirtualDevice::new(0x1000),
+        }
+    }
+}
+
+impl VirtualGamepad {
+    /// Reads the keyboard and mouse, called at the start of every frame.
+    pub(crate) fn frame(&mut self, ctx: &egui::Context) {
+        // typing into a text field must not drive the robot, nor keys held while focus was lost.
+        let active: bool = !ctx.wants_keyboard_input() && ctx.input(|i| i.focused);
+        let (keys, modifiers): (Vec<Key>, Modifiers) = if active {
+            ctx.input(|i| (i.keys_down.iter().copied().collect(), i.modifiers))
+        } else {
+            (Vec::new(), Modifiers::NONE)
+        };
+
+        let mut buttons: Vec<(Button, bool)> = BUTTON_KEYS
+            .iter()
+            .map(|(key, button)| (*button, keys.contains(key)))
+            .collect();
+        // shift holds the turbo modifier.
+        buttons.push((Button::LeftTrigger, modifiers.shift));
+        for (button, pressed) in buttons {
+            self.device.set_button(button, pressed);
+        }
+
+        for (axis, positive, negative) in AXIS_KEYS {
+            let pushed = |keys_of: [Key; 2]| -> f32 {
+                f32::from(u8::from(keys_of.iter().any(|key| keys.contains(key))))
+            };
+            self.device
+                .set_axis(axis, pushed(positive) - pushed(negative));
+        }
+
+        // a drag that started on the stick moves it, also once the panel is no longer drawn.
+        let pad: Option<Rect> = self.pad.take();
+        self.stick = ctx.input(|i| {
+            match (pad, i.pointer.press_origin(), i.pointer.interact_pos()) {
+                (Some(pad), Some(origin), Some(pointer))
+                    if i.pointer.primary_down() && pad.contains(origin) =>
+                {
+                    deflection(pad, pointer)
+                }
+                // springs back like a real stick.
+                _ => Vec2::ZERO,
+            }
+        });
+        self.device.set_axis(Axis::RightStickX, self.stick.x);
+        self.device.set_axis(Axis::RightStickY, self.stick.y);
+    }
+
+    pub(crate) fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.vertical_centered(|ui| {
+            ui.heading("⌨ Keyboard & Mouse");
+        });
+
+        ui.separator();
+
+        egui::Grid::new("virtual_gamepad_keys_grid")
+            .striped(true)
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("W A S D / Arrows");
+                ui.label(format!("{:?} {:?}", Axis::LeftStickX, Axis::LeftStickY));
+                ui.end_row();
+
+                ui.label("Shift");
+                ui.label(format!("{:?}", Button::LeftTrigger));
+                ui.end_row();
+
+                for (key, button) in BUTTON_KEYS {
+                    ui.label(key.name());
+                    ui.label(format!("{button:?}"));
+                    ui.end_row();
+                }
+
+                ui.label("Drag below");
+                ui.label(format!("{:?} {:?}", Axis::RightStickX, Axis::RightStickY));
+                ui.end_row();
+            });
+
+        ui.separator();
+
+        // the drag is only claimed here so it doesn't scroll the panel, `frame` reads the mouse.
+        let (rect, _) = ui.allocate_exact_size(Vec2::splat(150f32), Sense::drag());
+        self.pad = Some(rect);
+        let radius: f32 = rect.width() / 2f32;
+
+        let painter: &egui::Painter = ui.painter();
+        let stroke: Stroke = ui.visuals().widgets.inactive.fg_stroke;
+        painter.circle_stroke(rect.center(), radius, stroke);
+        let knob: Pos2 = rect.center() + Vec2::new(self.stick.x, -self.stick.y) * radius;
+        painter.circle_filled(knob, 8f32, ui.visuals().selection.bg_fill);
+    }
+}
+
+/// Mouse stick position within the unit circle, y up.
+fn deflection(pad: Rect, pointer: Pos2) -> Vec2 {
+    let offset: Vec2 = (pointer - pad.center()) / (pad.width() / 2f32);
+    let offset: Vec2 = if offset.length() > 1f32 {
+        offset.normalized()
+    } else {
+        offset
+    };
+    Vec2::new(offset.x, -offset.y)
+}
+
+impl InputSource for VirtualGamepad {
+    fn poll(&mut self, _now: SystemTime) -> Vec<SourceEvent> {
+        self.device
+            .drain()
+            .into_iter()
+            .map(|event| SourceEvent {
+                device: Device::Keyboard,
+                model: None,
+                event,
+            })
+            .collect()
+    }
+
+    fn devices(&self) -> Vec<(Device, String)> {
+        vec![(Device::Keyboard, "⌨ Keyboard & Mouse".to_string())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::{Event, PointerButton, RawInput};
+
+    use super::*;
+    use crate::input::event::InputEventKind;
+
+    fn frame(gamepad: &mut VirtualGamepad, ctx: &egui::Context, events: Vec<Event>) {
+        ctx.begin_frame(RawInput {
+            events,
+            ..RawInput::default()
+        });
+        gamepad.frame(ctx);
+        let _ = ctx.end_frame();
+    }
+
+    fn press(key: Key) -> Event {
+        Event::Key {
+            key,
+            physical_key: None,
+            pressed: true,
+            repeat: false,
+            modifiers: Modifiers::NONE,
+        }
+    }
+
+    #[test]
+    fn select_is_not_on_the_focus_key() {
+        let ctx: egui::Context = egui::Context::default();
+        let mut gamepad: VirtualGamepad = VirtualGamepad::default();
+        frame(&mut gamepad, &ctx, vec![press(Key::Tab), press(Key::C)]);
+
+        let pressed: Vec<Button> = gamepad
+            .poll(SystemTime::UNIX_EPOCH)
+            .into_iter()
+            .filter_map(|event| match event.event.kind {
+                InputEventKind::ButtonPressed { button, .. } => Some(button),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(pressed, vec![Button::Select]);
+    }
+
+    #[test]
+    fn mouse_stick_follows_the_drag_until_the_panel_closes() {
+        let ctx: egui::Context = egui::Context::default();
+        let mut gamepad: VirtualGamepad = VirtualGamepad::default();
+        let pad: Rect = Rect::from_center_size(Pos2::new(100.0, 100.0), Vec2::splat(100.0));
+        let pos: Pos2 = pad.center();
+
+        gamepad.pad = Some(pad);
+        frame(
+            &mut gamepad,
+            &ctx,
+            vec![
+                Event::PointerMoved(pos),
+                Event::PointerButton {
+                    pos,
+                    button: PointerButton::Primary,
+                    pressed: true,
+                    modifiers: Modifiers::NONE,
+                },
+                Event::PointerMoved(pos + Vec2::new(25.0, -25.0)),
+            ],
+        );
+        assert_eq!(gamepad.stick, Vec2::new(0.5, 0.5));
+
+        // still held, but the stick is no longer on screen.
+        frame(&mut gamepad, &ctx, Vec::new());
+        assert_eq!(gamepad.stick, Vec2::ZERO);
+    }
+}
@@ -0,0 +1,433 @@
+use gilrs::{ev::Code, Axis, Button, Gamepad, GamepadId, Gilrs, Mapping};
+
+use crate::input::event::InputEventKind;
+
+// axis deflection that counts as a deliberate move while mapping.
+const AXIS_THRESHOLD: f32 = 0.7;
+
+// the standard SDL elements, in the order the operator is asked for them.
+const STEPS: [Element; 21] = [
+    Element::Button(Button::South),
+    Element::Button(Button::East),
+    Element::Button(Button::West),
+    Element::Button(Button::North),
+    Element::Button(Button::LeftTrigger),
+    Element::Button(Button::RightTrigger),
+    Element::Trigger(Button::LeftTrigger2, Axis::LeftZ),
+    Element::Trigger(Button::RightTrigger2, Axis::RightZ),
+    Element::Button(Button::Select),
+    Element::Button(Button::Start),
+    Element::Button(Button::Mode),
+    Element::Button(Button::LeftThumb),
+    Element::Button(Button::RightThumb),
+    Element::DPad(Button::DPadUp, Axis::DPadY),
+    Element::DPad(Button::DPadDown, Axis::DPadY),
+    Element::DPad(Button::DPadLeft, Axis::DPadX),
+    Element::DPad(Button::DPadRight, Axis::DPadX),
+    Element::Axis(Axis::LeftStickX),
+    Element::Axis(Axis::LeftStickY),
+    Element::Axis(Axis::RightStickX),
+    Element::Axis(Axis::RightStickY),
+];
+
+#[derive(Clone, Copy)]
+enum Element {
+    Button(Button),
+    Axis(Axis),
+    // digital triggers map to the button, analog ones to the axis.
+    Trigger(Button, Axis),
+    // buttons or a hat, whose axis is shared by both directions.
+    DPad(Button, Axis),
+}
+
+impl Element {
+    fn prompt(&self) -> String {
+        match self {
+            Element::Button(button) | Element::DPad(button, _) => format!("Press {button:?}"),
+            Element::Axis(axis) => format!("Move {axis:?} all the way"),
+            Element::Trigger(button, _) => format!("Pull {button:?} all the way"),
+        }
+    }
+}
+
+/// Mapping recorded for one controller, applied again whenever it connects.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+struct SavedMapping {
+    uuid: [u8; 16],
+    name: String,
+    buttons: Vec<(Button, Code)>,
+    axes: Vec<(Axis, Code)>,
+    // SDL_GameControllerDB line generated by gilrs.
+    sdl: String,
+}
+
+impl SavedMapping {
+    fn data(&self) -> Mapping {
+        let mut data: Mapping = Mapping::new();
+        for (button, code) in &self.buttons {
+            data.insert_btn(*code, *button);
+        }
+        for (axis, code) in &self.axes {
+            data.insert_axis(*code, *axis);
+        }
+        data
+    }
+}
+
+// gilrs codes of the mapped buttons and axes.
+type Codes = (Vec<(Button, Code)>, Vec<(Axis, Code)>);
+
+/// Codes recorded for the steps so far.
+#[derive(Default)]
+struct Recorded {
+    step: usize,
+    // raw codes, turned back into gilrs codes when the mapping is applied.
+    buttons: Vec<(Button, u32)>,
+    // a hat axis is recorded once for each of its directions.
+    axes: Vec<(Axis, u32)>,
+}
+
+impl Recorded {
+    fn assigned(&self, code: u32) -> bool {
+        self.buttons.iter().any(|(_, c)| *c == code) || self.axes.iter().any(|(_, c)| *c == code)
+    }
+
+    fn record(&mut self, event: &InputEventKind) {
+        let Some(element) = STEPS.get(self.step) else {
+            return;
+        };
+        match (element, *event) {
+            (
+                Element::Button(button) | Element::Trigger(button, _) | Element::DPad(button, _),
+                InputEventKind::ButtonPressed { code, .. },
+            ) if !self.assigned(code) => {
+                self.buttons.push((*button, code));
+                self.step += 1;
+            }
+            (
+                Element::Axis(axis) | Element::Trigger(_, axis),
+                InputEventKind::AxisChanged { code, value, .. },
+            ) if value.abs() >= AXIS_THRESHOLD && !self.assigned(code) => {
+                self.axes.push((*axis, code));
+                self.step += 1;
+            }
+            // the other direction of a hat moves the axis that was already recorded.
+            (Element::DPad(_, axis), InputEventKind::AxisChanged { code, value, .. })
+                if value.abs() >= AXIS_THRESHOLD
+                    && (!self.assigned(code) || self.axes.contains(&(*axis, code))) =>
+            {
+                self.axes.push((*axis, code));
+                self.step += 1;
+            }
+            _ => (),
+        }
+    }
+
+    /// Steps back and forgets what the previous step recorded.
+    fn back(&mut self) {
+        self.step -= 1;
+        let (button, axis): (Option<Button>, Option<Axis>) = match STEPS[self.step] {
+            Element::Button(button) => (Some(button), None),
+            Element::Axis(axis) => (None, Some(axis)),
+            Element::Trigger(button, axis) | Element::DPad(button, axis) => {
+                (Some(button), Some(axis))
+            }
+        };
+        if let Some(button) = button {
+            self.buttons.retain(|(b, _)| *b != button);
+        }
+        // only the latest entry, the other direction of a hat keeps its own.
+        if let Some(index) = axis.and_then(|axis| self.axes.iter().rposition(|(a, _)| *a == axis)) {
+            self.axes.remove(index);
+        }
+    }
+}
+
+struct Editor {
+    gamepad: GamepadId,
+    recorded: Recorded,
+}
+
+impl Editor {
+    /// Looks the recorded codes up among the ones the gamepad reported.
+    fn finish(&self, gamepad: &Gamepad) -> Option<Codes> {
+        let button_code = |code: u32| -> Option<Code> {
+            gamepad
+                .state()
+                .buttons()
+                .map(|(c, _)| c)
+                .find(|c| c.into_u32() == code)
+        };
+        let axis_code = |code: u32| -> Option<Code> {
+            gamepad
+                .state()
+                .axes()
+                .map(|(c, _)| c)
+                .find(|c| c.into_u32() == code)
+        };
+        let buttons: Option<Vec<(Button, Code)>> = self
+            .recorded
+            .buttons
+            .iter()
+            .map(|(button, code)| button_code(*code).map(|code| (*button, code)))
+            .collect();
+        let axes: Option<Vec<(Axis, Code)>> = self
+            .recorded
+            .axes
+            .iter()
+            .map(|(axis, code)| axis_code(*code).map(|code| (*axis, code)))
+            .collect();
+        buttons.zip(axes)
+    }
+}
+
+/// SDL mappings made in the app for controllers gilrs does not recognize.
+#[derive(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub(crate) struct SdlMappings {
+    mappings: Vec<SavedMapping>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    editor: Option<Editor>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    error: Option<String>,
+    // saved mappings were applied to the controllers present at startup.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    started: bool,
+    // controllers whose mapping was forgotten, gilrs can't undo a mapping it applied.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    forgotten: Vec<[u8; 16]>,
+}
+
+impl SdlMappings {
+    fn get(&self, uuid: [u8; 16]) -> Option<&SavedMapping> {
+        self.mappings.iter().find(|mapping| mapping.uuid == uuid)
+    }
+
+    /// Whether events of this gamepad are being recorded and must not reach the robot.
+    pub(crate) fn recording(&self, gamepad: GamepadId) -> bool {
+        self.editor
+            .as_ref()
+            .is_some_and(|editor| editor.gamepad == gamepad)
+    }
+
+    pub(crate) fn record(&mut self, event: &InputEventKind) {
+        if let Some(editor) = &mut self.editor {
+            editor.recorded.record(event);
+        }
+    }
+
+    /// Applies the saved mappings to controllers that were connected before the app started.
+    pub(crate) fn start(&mut self, gilrs: &mut Gilrs) {
+        if self.started {
+            return;
+        }
+        self.started = true;
+        let connected: Vec<GamepadId> = gilrs.gamepads().map(|(id, _)| id).collect();
+        for gamepad in connected {
+            self.connected(gilrs, gamepad);
+        }
+    }
+
+    /// Applies the saved mapping of a controller that just connected.
+    pub(crate) fn connected(&mut self, gilrs: &mut Gilrs, gamepad: GamepadId) {
+        let uuid: [u8; 16] = gilrs.gamepad(gamepad).uuid();
+        if let Some(mapping) = self.get(uuid) {
+            if let Err(error) =
+                gilrs.set_mapping(gamepad.into(), &mapping.data(), mapping.name.as_str())
+            {
+                self.error = Some(format!("Failed to apply the saved mapping: {error}"));
+            }
+        }
+    }
+
+    pub(crate) fn ui(&mut self, ui: &mut egui::Ui, gilrs: &mut Gilrs, gamepad_id: GamepadId) {
+        let gamepad: Gamepad = gilrs.gamepad(gamepad_id);
+        let uuid: [u8; 16] = gamepad.uuid();
+        // commas separate the fields of a mapping line.
+        let name: String = gamepad.name().replace(',', " ");
+
+        let mut finished: bool = false;
+        let mut cancelled: bool = false;
+        match &mut self.editor {
+            Some(editor) if editor.gamepad == gamepad_id => {
+                let recorded: &mut Recorded = &mut editor.recorded;
+                match STEPS.get(recorded.step) {
+                    Some(element) => {
+                        ui.label(format!(
+                            "{}/{} {}",
+                            recorded.step + 1,
+                            STEPS.len(),
+                            element.prompt()
+                        ));
+                    }
+                    None => {
+                        ui.label("All elements are recorded.");
+                    }
+                }
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(recorded.step < STEPS.len(), egui::Button::new("Skip ⏩"))
+                        .on_hover_text("The controller has no such element.")
+                        .clicked()
+                    {
+                        recorded.step += 1;
+                    }
+                    if ui
+                        .add_enabled(recorded.step > 0, egui::Button::new("⏪ Back"))
+                        .clicked()
+                    {
+                        recorded.back();
+                    }
+                    finished = ui
+                        .add_enabled(
+                            !recorded.buttons.is_empty() || !recorded.axes.is_empty(),
+                            egui::Button::new("✔ Apply"),
+                        )
+                        .clicked();
+                    cancelled = ui.button("Cancel").clicked();
+                });
+            }
+            _ => {
+                ui.horizontal(|ui| {
+                    if ui.button("🎮 Map Controller").clicked() {
+                        self.editor = Some(Editor {
+                            gamepad: gamepad_id,
+                            recorded: Recorded::default(),
+                        });
+                        self.error = None;
+                    }
+                    if ui
+                        .add_enabled(self.get(uuid).is_some(), egui::Button::new("🗑 Forget"))
+                        .on_hover_text("Takes effect the next time the app starts.")
+                        .clicked()
+                    {
+                        self.mappings.retain(|mapping| mapping.uuid != uuid);
+                        self.forgotten.push(uuid);
+                    }
+                });
+            }
+        }
+
+        if finished {
+            let codes: Option<Codes> = self
+                .editor
+                .take()
+                .and_then(|editor| editor.finish(&gilrs.gamepad(gamepad_id)));
+            if codes.is_none() {
+                self.error =
+                    Some("The controller no longer reports a recorded element.".to_string());
+            }
+            if let Some((buttons, axes)) = codes {
+                let mut mapping: SavedMapping = SavedMapping {
+                    uuid,
+                    name,
+                    buttons,
+                    axes,
+                    sdl: String::new(),
+                };
+                match gilrs.set_mapping(gamepad_id.into(), &mapping.data(), mapping.name.as_str()) {
+                    Ok(sdl) => {
+                        mapping.sdl = sdl;
+                        self.forgotten.retain(|u| *u != uuid);
+                        self.mappings.retain(|m| m.uuid != uuid);
+                        self.mappings.push(mapping);
+                        self.error = None;
+                    }
+                    Err(error) => {
+                        self.error = Some(format!("Failed to apply the mapping: {error}"))
+                    }
+                }
+            }
+        }
+        if cancelled {
+            self.editor = None;
+        }
+
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+        if self.forgotten.contains(&uuid) {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                "⚠ The forgotten mapping stays applied to this controller until the app restarts.",
+            );
+        }
+
+        if let Some(mapping) = self.get(uuid) {
+            ui.label("SDL_GameControllerDB");
+            ui.horizontal(|ui| {
+                let mut sdl: &str = mapping.sdl.as_str();
+                ui.add(egui::TextEdit::singleline(&mut sdl).code_editor());
+                if ui.button("📋").on_hover_text("Copy").clicked() {
+                    ui.output_mut(|output| output.copied_text = mapping.sdl.clone());
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn axis(axis: Axis, code: u32, value: f32) -> InputEventKind {
+        InputEventKind::AxisChanged { axis, code, value }
+    }
+
+    // records every step up to the d-pad as a button, digital triggers included.
+    fn at_the_dpad() -> Recorded {
+        let mut recorded: Recorded = Recorded::default();
+        for code in 1..=13 {
+            recorded.record(&InputEventKind::ButtonPressed {
+                button: Button::Unknown,
+                code,
+            });
+        }
+        recorded
+    }
+
+    #[test]
+    fn dpad_accepts_a_hat_for_both_directions() {
+        let mut recorded: Recorded = at_the_dpad();
+        let dpad: usize = recorded.step;
+        assert!(matches!(STEPS[dpad], Element::DPad(Button::DPadUp, _)));
+
+        recorded.record(&axis(Axis::Unknown, 100, -1.0));
+        recorded.record(&axis(Axis::Unknown, 100, 1.0));
+        // a small wobble of the other axis isn't a move.
+        recorded.record(&axis(Axis::Unknown, 101, 0.2));
+        recorded.record(&axis(Axis::Unknown, 101, -1.0));
+        recorded.record(&axis(Axis::Unknown, 101, 1.0));
+        assert_eq!(recorded.step, dpad + 4);
+        assert_eq!(
+            recorded.axes,
+            vec![
+                (Axis::DPadY, 100),
+                (Axis::DPadY, 100),
+                (Axis::DPadX, 101),
+                (Axis::DPadX, 101)
+            ]
+        );
+
+        recorded.back();
+        assert_eq!(recorded.axes.len(), 3);
+    }
+
+    #[test]
+    fn dpad_accepts_buttons_but_not_a_used_axis() {
+        let mut recorded: Recorded = at_the_dpad();
+        let dpad: usize = recorded.step;
+
+        recorded.record(&InputEventKind::ButtonPressed {
+            button: Button::Unknown,
+            code: 20,
+        });
+        // a stick that is mapped already can't be the d-pad.
+        recorded.axes.push((Axis::LeftStickX, 30));
+        recorded.record(&axis(Axis::Unknown, 30, 1.0));
+
+        assert_eq!(recorded.step, dpad + 1);
+        assert_eq!(recorded.buttons.last(), Some(&(Button::DPadUp, 20)));
+    }
+}
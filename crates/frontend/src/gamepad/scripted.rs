@@ -0,0 +1,210 @@
+use std::time::{Duration, SystemTime};
+
+use egui::RichText;
+use gilrs::{Axis, Button};
+
+use crate::gamepad::virtual_device::VirtualDevice;
+use crate::input::event::InputEvent;
+use crate::input::source::{Device, InputSource, SourceEvent};
+
+/// Element state to set once the script has run for `at` seconds.
+#[derive(Clone, Copy)]
+pub(crate) struct ScriptStep {
+    pub(crate) at: f32,
+    pub(crate) element: ScriptElement,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum ScriptElement {
+    Button(Button, bool),
+    Axis(Axis, f32),
+}
+
+impl ScriptStep {
+    pub(crate) fn axis(at: f32, axis: Axis, value: f32) -> Self {
+        Self {
+            at,
+            element: ScriptElement::Axis(axis, value),
+        }
+    }
+
+    pub(crate) fn button(at: f32, button: Button, pressed: bool) -> Self {
+        Self {
+            at,
+            element: ScriptElement::Button(button, pressed),
+        }
+    }
+}
+
+// drives forward, turns, stops and shifts a gear up and back down.
+fn demo_script() -> Vec<ScriptStep> {
+    vec![
+        ScriptStep::axis(0.0, Axis::LeftStickY, 0.5),
+        ScriptStep::axis(1.0, Axis::LeftStickX, 0.5),
+        ScriptStep::axis(2.0, Axis::LeftStickX, 0.0),
+        ScriptStep::axis(2.0, Axis::LeftStickY, 0.0),
+        ScriptStep::button(3.0, Button::DPadUp, true),
+        ScriptStep::button(3.1, Button::DPadUp, false),
+        ScriptStep::button(4.0, Button::DPadDown, true),
+        ScriptStep::button(4.1, Button::DPadDown, false),
+    ]
+}
+
+/// Plays synthetic events, exercises the input pipeline without any hardware.
+pub(crate) struct ScriptedSource {
+    script: Vec<ScriptStep>,
+    playing: bool,
+    // taken from the first poll after play was pressed.
+    start: Option<SystemTime>,
+    // index of the next step to play.
+    next: usize,
+    device: VirtualDevice,
+}
+
+impl Default for ScriptedSource {
+    fn default() -> Self {
+        Self::new(demo_script())
+    }
+}
+
+impl ScriptedSource {
+    pub(crate) fn new(script: Vec<ScriptStep>) -> Self {
+        Self {
+            script,
+            playing: false,
+            start: None,
+            next: 0,
+            device: VirtualDevice::new(0x3000),
+        }
+    }
+
+    pub(crate) fn play(&mut self) {
+        self.playing = true;
+        self.start = None;
+        self.next = 0;
+    }
+
+    /// Stops playing and lets go of everything the script touched.
+    pub(crate) fn stop(&mut self) {
+        self.playing = false;
+        for step in &self.script {
+            match step.element {
+                ScriptElement::Button(button, _) => self.device.set_button(button, false),
+                ScriptElement::Axis(axis, _) => self.device.set_axis(axis, 0f32),
+            }
+        }
+    }
+
+    /// Sets an element right away, outside of the script.
+    pub(crate) fn inject(&mut self, element: ScriptElement) {
+        match element {
+            ScriptElement::Button(button, pressed) => self.device.set_button(button, pressed),
+            ScriptElement::Axis(axis, value) => self.device.set_axis(axis, value),
+        }
+    }
+
+    pub(crate) fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.vertical_centered(|ui| {
+            ui.heading("🧪 Scripted");
+        });
+
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            if self.playing {
+                if ui.button("⏹ Stop").clicked() {
+                    self.stop();
+                }
+            } else if ui.button("▶ Play").clicked() {
+                self.play();
+            }
+            ui.label(format!("{}/{} steps", self.next, self.script.len()));
+        });
+
+        egui::Grid::new("scripted_steps_grid")
+            .striped(true)
+            .num_columns(2)
+            .show(ui, |ui| {
+                for (index, step) in self.script.iter().enumerate() {
+                    let text: String = match step.element {
+                        ScriptElement::Button(button, true) => format!("press {button:?}"),
+                        ScriptElement::Button(button, false) => format!("release {button:?}"),
+                        ScriptElement::Axis(axis, value) => format!("{axis:?} {value:+.2}"),
+                    };
+                    let text: RichText = RichText::new(text).monospace();
+                    ui.monospace(format!("{:.1} s", step.at));
+                    ui.label(if index < self.next { text.weak() } else { text });
+                    ui.end_row();
+                }
+            });
+    }
+}
+
+impl InputSource for ScriptedSource {
+    fn poll(&mut self, now: SystemTime) -> Vec<SourceEvent> {
+        if self.playing {
+            let start: SystemTime = *self.start.get_or_insert(now);
+            let elapsed: Duration = now.duration_since(start).unwrap_or_default();
+            while let Some(step) = self.script.get(self.next) {
+                if elapsed.as_secs_f32() < step.at {
+                    break;
+                }
+                self.inject(step.element);
+                self.next += 1;
+            }
+            if self.next >= self.script.len() {
+                self.playing = false;
+            }
+        }
+
+        self.device
+            .drain()
+            .into_iter()
+            .map(|event| SourceEvent {
+                device: Device::Scripted,
+                model: None,
+                // played on the loop's clock, not the wall clock the device stamps.
+                event: InputEvent { time: now, ..event },
+            })
+            .collect()
+    }
+
+    fn devices(&self) -> Vec<(Device, String)> {
+        vec![(Device::Scripted, "🧪 Scripted".to_string())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::event::InputEventKind;
+
+    fn at(seconds: f64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs_f64(seconds)
+    }
+
+    fn values(events: Vec<SourceEvent>) -> Vec<(SystemTime, f32)> {
+        events
+            .into_iter()
+            .filter_map(|SourceEvent { event, .. }| match event.kind {
+                InputEventKind::AxisChanged { value, .. } => Some((event.time, value)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn steps_play_on_the_polling_clock() {
+        let mut source: ScriptedSource = ScriptedSource::new(vec![
+            ScriptStep::axis(0.0, Axis::LeftStickY, 0.5),
+            ScriptStep::axis(1.0, Axis::LeftStickY, 0.0),
+        ]);
+        assert!(source.poll(at(10.0)).is_empty());
+
+        source.play();
+        assert_eq!(values(source.poll(at(20.0))), vec![(at(20.0), 0.5)]);
+        assert!(source.poll(at(20.5)).is_empty());
+        assert_eq!(values(source.poll(at(21.0))), vec![(at(21.0), 0.0)]);
+        assert!(!source.playing);
+    }
+}
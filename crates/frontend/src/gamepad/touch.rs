@@ -0,0 +1,374 @@
+use std::collections::BTreeMap;
+use std::time::SystemTime;
+
+use egui::{
+    Align2, Color32, DragValue, Event, FontId, Id, Order, Pos2, Rect, Sense, Stroke, TouchId,
+    TouchPhase, Vec2,
+};
+use gilrs::{Axis, Button};
+
+use crate::gamepad::virtual_device::VirtualDevice;
+use crate::input::source::{Device, InputSource, SourceEvent};
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+enum TouchElement {
+    Stick { x: Axis, y: Axis },
+    Button(Button),
+}
+
+impl TouchElement {
+    fn name(&self) -> String {
+        match self {
+            TouchElement::Stick {
+                x: Axis::LeftStickX,
+                ..
+            } => "Left Stick".to_string(),
+            TouchElement::Stick {
+                x: Axis::RightStickX,
+                ..
+            } => "Right Stick".to_string(),
+            TouchElement::Stick { x, y } => format!("{x:?} {y:?}"),
+            TouchElement::Button(button) => format!("{button:?}"),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+struct TouchControl {
+    element: TouchElement,
+    // center, as a fraction of the screen so the layout survives rotation.
+    position: Pos2,
+    // diameter in points.
+    size: f32,
+}
+
+impl TouchControl {
+    fn stick(x: Axis, y: Axis, position: Pos2) -> Self {
+        Self {
+            element: TouchElement::Stick { x, y },
+            position,
+            size: 160.0,
+        }
+    }
+
+    fn button(button: Button, position: Pos2) -> Self {
+        Self {
+            element: TouchElement::Button(button),
+            position,
+            size: 64.0,
+        }
+    }
+
+    fn center(&self, screen: Rect) -> Pos2 {
+        screen.min + self.position.to_vec2() * screen.size()
+    }
+
+    fn rect(&self, screen: Rect) -> Rect {
+        Rect::from_center_size(self.center(screen), Vec2::splat(self.size))
+    }
+}
+
+fn default_layout() -> Vec<TouchControl> {
+    vec![
+        TouchControl::stick(Axis::LeftStickX, Axis::LeftStickY, Pos2::new(0.15, 0.75)),
+        TouchControl::stick(Axis::RightStickX, Axis::RightStickY, Pos2::new(0.85, 0.75)),
+        TouchControl::button(Button::Start, Pos2::new(0.5, 0.85)),
+        TouchControl::button(Button::Select, Pos2::new(0.4, 0.85)),
+        TouchControl::button(Button::LeftTrigger, Pos2::new(0.15, 0.5)),
+        TouchControl::button(Button::DPadUp, Pos2::new(0.7, 0.5)),
+        TouchControl::button(Button::DPadDown, Pos2::new(0.7, 0.62)),
+        TouchControl::button(Button::North, Pos2::new(0.85, 0.45)),
+        TouchControl::button(Button::South, Pos2::new(0.85, 0.55)),
+    ]
+}
+
+/// Multi-touch on-screen sticks and buttons for phones and tablets without a controller.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub(crate) struct TouchGamepad {
+    visible: bool,
+    controls: Vec<TouchControl>,
+    // dragging a control moves it instead of pressing it.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    editing: bool,
+    // control each finger went down on and where it is now.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    touches: BTreeMap<TouchId, (usize, Pos2)>,
+    #[cfg_attr(feature = "serde", serde(skip, default = "touch_device"))]
+    device: VirtualDevice,
+}
+
+fn touch_device() -> VirtualDevice {
+    VirtualDevice::new(0x2000)
+}
+
+impl Default for TouchGamepad {
+    fn default() -> Self {
+        Self {
+            visible: false,
+            controls: default_layout(),
+            editing: false,
+            touches: BTreeMap::new(),
+            device: touch_device(),
+        }
+    }
+}
+
+impl TouchGamepad {
+    /// Tracks the fingers and draws the controls over everything else, called every frame.
+    pub(crate) fn frame(&mut self, ctx: &egui::Context) {
+        let screen: Rect = ctx.screen_rect();
+        if !self.visible {
+            self.touches.clear();
+            self.send(screen);
+            return;
+        }
+
+        let touches: Vec<(TouchId, TouchPhase, Pos2)> = ctx.input(|i| {
+            i.events
+                .iter()
+                .filter_map(|event| match event {
+                    Event::Touch { id, phase, pos, .. } => Some((*id, *phase, *pos)),
+                    _ => None,
+                })
+                .collect()
+        });
+        for (id, phase, pos) in touches {
+            match phase {
+                TouchPhase::Start => {
+                    if let Some(index) = self.controls.iter().rposition(|control| {
+                        control.center(screen).distance(pos) <= control.size / 2f32
+                    }) {
+                        self.touches.insert(id, (index, pos));
+                    }
+                }
+                TouchPhase::Move => {
+                    if let Some((index, last)) = self.touches.get_mut(&id) {
+                        if self.editing {
+                            let control: &mut TouchControl = &mut self.controls[*index];
+                            let moved: Vec2 = (pos - *last) / screen.size();
+                            control.position =
+                                (control.position + moved).clamp(Pos2::ZERO, Pos2::new(1.0, 1.0));
+                        }
+                        *last = pos;
+                    }
+                }
+                TouchPhase::End | TouchPhase::Cancel => {
+                    self.touches.remove(&id);
+                }
+            }
+        }
+
+        self.send(screen);
+        self.paint(ctx, screen);
+    }
+
+    /// Current stick deflection of a control, y up, `None` when no finger is on it.
+    fn deflection(&self, index: usize, screen: Rect) -> Option<Vec2> {
+        if self.editing {
+            return None;
+        }
+        let control: &TouchControl = &self.controls[index];
+        // the newest finger wins when several are on one control.
+        self.touches
+            .values()
+            .rev()
+            .find(|(i, _)| *i == index)
+            .map(|(_, pos)| {
+                let offset: Vec2 = (*pos - control.center(screen)) / (control.size / 2f32);
+                let offset: Vec2 = if offset.length() > 1f32 {
+                    offset.normalized()
+                } else {
+                    offset
+                };
+                Vec2::new(offset.x, -offset.y)
+            })
+    }
+
+    fn send(&mut self, screen: Rect) {
+        for index in 0..self.controls.len() {
+            let deflection: Option<Vec2> = self.deflection(index, screen);
+            match self.controls[index].element {
+                TouchElement::Button(button) => {
+                    self.device.set_button(button, deflection.is_some());
+                }
+                // springs back once the finger lifts.
+                TouchElement::Stick { x, y } => {
+                    let deflection: Vec2 = deflection.unwrap_or(Vec2::ZERO);
+                    self.device.set_axis(x, deflection.x);
+                    self.device.set_axis(y, deflection.y);
+                }
+            }
+        }
+    }
+
+    fn paint(&self, ctx: &egui::Context, screen: Rect) {
+        for (index, control) in self.controls.iter().enumerate() {
+            let deflection: Option<Vec2> = self.deflection(index, screen);
+
+            let rect: Rect = control.rect(screen);
+            egui::Area::new(Id::new(("touch_control", index)))
+                .order(Order::Foreground)
+                .fixed_pos(rect.min)
+                .show(ctx, |ui| {
+                    // claims the touch so the widgets below don't react to it.
+                    ui.allocate_rect(rect, Sense::drag());
+
+                    let painter: &egui::Painter = ui.painter();
+                    let active: bool = self.touches.values().any(|(i, _)| *i == index);
+                    let fill: Color32 = if active {
+                        ui.visuals().selection.bg_fill.gamma_multiply(0.6)
+                    } else {
+                        ui.visuals().widgets.inactive.bg_fill.gamma_multiply(0.4)
+                    };
+                    let stroke: Stroke = if self.editing {
+                        Stroke::new(2f32, ui.visuals().warn_fg_color)
+                    } else {
+                        ui.visuals().widgets.inactive.fg_stroke
+                    };
+                    let radius: f32 = control.size / 2f32;
+                    painter.circle(rect.center(), radius, fill, stroke);
+                    match control.element {
+                        TouchElement::Stick { .. } => {
+                            let knob: Vec2 = deflection.unwrap_or(Vec2::ZERO);
+                            painter.circle_filled(
+                                rect.center() + Vec2::new(knob.x, -knob.y) * radius,
+                                radius / 3f32,
+                                ui.visuals().selection.bg_fill,
+                            );
+                        }
+                        TouchElement::Button(_) => {
+                            painter.text(
+                                rect.center(),
+                                Align2::CENTER_CENTER,
+                                control.element.name(),
+                                FontId::proportional(radius / 2.5),
+                                ui.visuals().text_color(),
+                            );
+                        }
+                    }
+                });
+        }
+    }
+
+    pub(crate) fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.vertical_centered(|ui| {
+            ui.heading("📱 Touch Screen");
+        });
+
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.visible, "Show Controls");
+            ui.add_enabled_ui(self.visible, |ui| {
+                ui.toggle_value(&mut self.editing, "✋ Edit Layout")
+                    .on_hover_text(
+                        "Drag the controls to move them, they don't send input meanwhile.",
+                    );
+            });
+            if ui.button("↺ Reset Layout").clicked() {
+                self.controls = default_layout();
+            }
+        });
+
+        egui::Grid::new("touch_controls_grid")
+            .striped(true)
+            .num_columns(2)
+            .show(ui, |ui| {
+                for control in self.controls.iter_mut() {
+                    ui.label(control.element.name());
+                    ui.add(
+                        DragValue::new(&mut control.size)
+                            .speed(1.0)
+                            .clamp_range(32.0..=320.0)
+                            .suffix(" pt"),
+                    );
+                    ui.end_row();
+                }
+            });
+    }
+}
+
+impl InputSource for TouchGamepad {
+    fn poll(&mut self, _now: SystemTime) -> Vec<SourceEvent> {
+        self.device
+            .drain()
+            .into_iter()
+            .map(|event| SourceEvent {
+                device: Device::Touch,
+                model: None,
+                event,
+            })
+            .collect()
+    }
+
+    fn devices(&self) -> Vec<(Device, String)> {
+        vec![(Device::Touch, "📱 Touch Screen".to_string())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::event::{InputEvent, InputEventKind};
+
+    fn screen() -> Rect {
+        Rect::from_min_size(Pos2::ZERO, Vec2::new(1000.0, 500.0))
+    }
+
+    // puts a finger on a control, offset from its center.
+    fn touch(gamepad: &mut TouchGamepad, index: usize, offset: Vec2) {
+        let pos: Pos2 = gamepad.controls[index].center(screen()) + offset;
+        gamepad.touches.insert(TouchId(index as u64), (index, pos));
+    }
+
+    #[test]
+    fn stick_deflection_points_up_and_stays_in_the_circle() {
+        let mut gamepad: TouchGamepad = TouchGamepad::default();
+        assert_eq!(gamepad.deflection(0, screen()), None);
+
+        touch(&mut gamepad, 0, Vec2::new(40.0, -40.0));
+        assert_eq!(gamepad.deflection(0, screen()), Some(Vec2::new(0.5, 0.5)));
+
+        touch(&mut gamepad, 0, Vec2::new(160.0, 0.0));
+        assert_eq!(gamepad.deflection(0, screen()), Some(Vec2::new(1.0, 0.0)));
+
+        // dragging moves the layout, it doesn't drive.
+        gamepad.editing = true;
+        assert_eq!(gamepad.deflection(0, screen()), None);
+    }
+
+    #[test]
+    fn buttons_send_a_press_and_a_release() {
+        let mut gamepad: TouchGamepad = TouchGamepad::default();
+        let start: usize = 2;
+        gamepad.send(screen());
+        gamepad.device.drain();
+
+        touch(&mut gamepad, start, Vec2::ZERO);
+        gamepad.send(screen());
+        // still held, nothing new.
+        gamepad.send(screen());
+        let pressed: Vec<InputEvent> = gamepad.device.drain();
+        assert_eq!(pressed.len(), 2);
+        assert!(matches!(
+            pressed[0].kind,
+            InputEventKind::ButtonPressed {
+                button: Button::Start,
+                ..
+            }
+        ));
+
+        gamepad.touches.clear();
+        gamepad.send(screen());
+        assert!(matches!(
+            gamepad.device.drain()[0].kind,
+            InputEventKind::ButtonReleased {
+                button: Button::Start,
+                ..
+            }
+        ));
+    }
+}
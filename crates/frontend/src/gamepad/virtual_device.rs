@@ -0,0 +1,70 @@
+use std::collections::BTreeMap;
+use std::time::SystemTime;
+
+use gilrs::{Axis, Button};
+use gilrs_core::utils::time_now;
+
+use crate::input::event::{InputEvent, InputEventKind};
+
+// axis codes follow the button codes of a device.
+const AXIS_CODES: u32 = 0x100;
+
+/// Turns the element states of an on-screen device into the events a real gamepad would send.
+pub(crate) struct VirtualDevice {
+    // raw codes of the device start here, far from anything a real gamepad reports.
+    codes: u32,
+    buttons: BTreeMap<u32, bool>,
+    axes: BTreeMap<u32, f32>,
+    events: Vec<InputEvent>,
+}
+
+impl VirtualDevice {
+    pub(crate) fn new(codes: u32) -> Self {
+        Self {
+            codes,
+            buttons: BTreeMap::new(),
+            axes: BTreeMap::new(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Sends press, release and value events when the state changed.
+    pub(crate) fn set_button(&mut self, button: Button, pressed: bool) {
+        let code: u32 = self.codes + button as u32;
+        if self.buttons.insert(code, pressed).unwrap_or_default() == pressed {
+            return;
+        }
+        let time: SystemTime = time_now();
+        let value: f32 = f32::from(u8::from(pressed));
+        let kind: InputEventKind = if pressed {
+            InputEventKind::ButtonPressed { button, code }
+        } else {
+            InputEventKind::ButtonReleased { button, code }
+        };
+        self.events.push(InputEvent { time, kind });
+        self.events.push(InputEvent {
+            time,
+            kind: InputEventKind::ButtonChanged {
+                button,
+                code,
+                value,
+            },
+        });
+    }
+
+    pub(crate) fn set_axis(&mut self, axis: Axis, value: f32) {
+        let code: u32 = self.codes + AXIS_CODES + axis as u32;
+        if self.axes.insert(code, value).unwrap_or_default() == value {
+            return;
+        }
+        self.events.push(InputEvent {
+            time: time_now(),
+            kind: InputEventKind::AxisChanged { axis, code, value },
+        });
+    }
+
+    /// Events since the last call, in order.
+    pub(crate) fn drain(&mut self) -> Vec<InputEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
@@ -1,48 +1,171 @@
+use std::time::SystemTime;
+
 use eframe::egui;
-use egui::Frame;
-use egui_dock::{DockArea, DockState, NodeIndex, Style, SurfaceIndex};
+use egui::{Frame, RichText};
+use egui_dock::{DockArea, DockState, NodeIndex, Style};
+use gilrs_core::utils::time_now;
 
+use crate::command::arm::{ArmRequest, ArmState};
+use crate::command::control_loop::ControlLoop;
+#[cfg(feature = "serde")]
 use crate::command::joints::JointState;
-use crate::gamepad::control_panel::GamepadControlPanel;
+use crate::command::recording::RecorderStatus;
+#[cfg(feature = "serde")]
+use crate::command::recording::{Recorder, RecorderRequest, Recording};
+#[cfg(feature = "serde")]
+use crate::file;
+use crate::input::binding::{Bindings, ControllerModel};
+use crate::input::filter::AxisFilters;
+use crate::input::role::Role;
+use crate::input::snapshot::InputSnapshot;
+use crate::command::speed_mode::SpeedMode;
+use crate::command::velocity::DriveCommand;
 use crate::wasm::info_panel::WasmInfoPanel;
 
+#[derive(Clone, Copy, PartialEq)]
+enum Tab {
+    Drive,
+    Arm,
+    Input,
+    Bindings,
+    Filters,
+    Oscilloscope,
+    Diagnostics,
+    RobotProfile,
+}
+
+// draws from copies of the controller, the loop keeps stepping while a frame is drawn.
 struct TabViewer<'a> {
-    added_nodes: &'a mut Vec<(SurfaceIndex, NodeIndex)>,
+    control_loop: &'a ControlLoop,
 }
 
 impl egui_dock::TabViewer for TabViewer<'_> {
-    type Tab = usize;
+    type Tab = Tab;
 
     fn title(&mut self, tab: &mut Self::Tab) -> egui::WidgetText {
-        format!("Tab {tab}").into()
+        match tab {
+            Tab::Drive => "🚗 Drive",
+            Tab::Arm => "🦾 Arm",
+            Tab::Input => "🎛 Input",
+            Tab::Bindings => "🎯 Bindings",
+            Tab::Filters => "〰 Filters",
+            Tab::Oscilloscope => "📈 Oscilloscope",
+            Tab::Diagnostics => "🩺 Diagnostics",
+            Tab::RobotProfile => "🤖 Robot Profile",
+        }
+        .into()
     }
 
     fn ui(&mut self, ui: &mut egui::Ui, tab: &mut Self::Tab) {
-        ui.label(format!("Content of tab {tab}"));
-    }
+        let control_loop: &ControlLoop = self.control_loop;
+        match tab {
+            Tab::Drive => {
+                control_loop.edit(
+                    |controller| &mut controller.joints.schemes,
+                    |schemes| schemes.ui(ui),
+                );
+
+                ui.separator();
+
+                let now: SystemTime = time_now();
+                let drive_command: DriveCommand =
+                    control_loop.read(|controller| controller.drive_command);
+                if drive_command.expired(now) {
+                    ui.colored_label(
+                        ui.visuals().error_fg_color,
+                        "⛔ The control loop stalled, the drive output is zeroed.",
+                    );
+                }
+                drive_command.current(now).ui(ui);
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.heading("🧭 Odometry");
+                    if ui.button("Reset").clicked() {
+                        control_loop.write(|controller| controller.joints.reset_odometry = true);
+                    }
+                });
+                control_loop.read(|controller| controller.odometry).ui(ui);
+
+                ui.separator();
 
-    fn on_add(&mut self, surface: SurfaceIndex, node: NodeIndex) {
-        self.added_nodes.push((surface, node));
+                ui.heading("⚙ Speed Modes");
+                control_loop.edit(
+                    |controller| &mut controller.joints.speed_modes,
+                    |speed_modes| speed_modes.ui(ui),
+                );
+            }
+            Tab::Arm => control_loop.edit(
+                |controller| &mut controller.robot_profile,
+                |profile| {
+                    let arm: ArmState =
+                        control_loop.read(|controller| controller.joints.arm.clone());
+                    let mut edited: ArmState = arm.clone();
+                    let request: Option<ArmRequest> = edited.ui(ui, profile);
+                    control_loop.write(|controller| {
+                        let arm_state: &mut ArmState = &mut controller.joints.arm;
+                        arm_state.merge(&arm, edited);
+                        if let Some(request) = request {
+                            arm_state.request(request, &controller.robot_profile);
+                        }
+                    });
+                },
+            ),
+            Tab::Input => {
+                let inputs: [InputSnapshot; 2] = control_loop.read(|controller| {
+                    Role::ALL.map(|role| controller.joints.role(role).input.clone())
+                });
+                for (role, input) in Role::ALL.into_iter().zip(inputs) {
+                    ui.heading(role.to_string());
+                    ui.push_id(role, |ui| input.ui(ui));
+                    ui.separator();
+                }
+            }
+            Tab::Bindings => {
+                let models: Vec<ControllerModel> =
+                    control_loop.read(|controller| controller.joints.models());
+                control_loop.edit(
+                    |controller| &mut controller.joints.bindings,
+                    |bindings| bindings.ui(ui, &models),
+                );
+            }
+            Tab::Filters => {
+                let filters: AxisFilters =
+                    control_loop.read(|controller| controller.joints.filters.clone());
+                let mut edited: AxisFilters = filters.clone();
+                edited.ui(ui);
+                control_loop.write(|controller| controller.joints.filters.merge(&filters, edited));
+            }
+            Tab::Oscilloscope => control_loop.shared(
+                |controller| &controller.oscilloscope,
+                |oscilloscope| oscilloscope.ui(ui),
+            ),
+            Tab::Diagnostics => {
+                control_loop.shared(|controller| &controller.latency, |latency| latency.ui(ui))
+            }
+            Tab::RobotProfile => control_loop.edit(
+                |controller| &mut controller.robot_profile,
+                |profile| profile.ui(ui),
+            ),
+        }
     }
 }
 
 struct Docks {
-    tree: DockState<usize>,
-    counter: usize,
+    tree: DockState<Tab>,
 }
 
 impl Default for Docks {
     fn default() -> Self {
-        let mut tree: DockState<usize> = DockState::new(vec![1]);
+        let mut tree: DockState<Tab> = DockState::new(vec![Tab::Drive, Tab::Arm]);
 
         // You can modify the tree before constructing the dock
-        let [a, b] = tree
+        let [_, _] = tree
             .main_surface_mut()
-            .split_left(NodeIndex::root(), 0.5, vec![2]);
-        let [_, _] = tree.main_surface_mut().split_below(a, 0.5, vec![3]);
-        let [_, _] = tree.main_surface_mut().split_below(b, 0.5, vec![4]);
+            .split_right(NodeIndex::root(), 0.5, vec![Tab::Input, Tab::Bindings, Tab::Filters, Tab::Oscilloscope, Tab::Diagnostics, Tab::RobotProfile]);
 
-        Self { tree, counter: 4 }
+        Self { tree }
     }
 }
 
@@ -51,10 +174,11 @@ impl Default for Docks {
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[cfg_attr(feature = "serde", serde(default))]
 pub struct State {
+    #[cfg_attr(feature = "serde", serde(skip))]
     docks: Docks,
-    gamepad_control_panel: GamepadControlPanel,
     wasm_info_panel: WasmInfoPanel,
-    joints: JointState,
+    // gamepads, joints and the robot profile, stepped by the control loop.
+    control_loop: ControlLoop,
 }
 
 #[derive(Default)]
@@ -63,21 +187,112 @@ pub struct HomePage {
 }
 
 impl HomePage {
-    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
-        Self::default()
+    #[cfg_attr(not(feature = "serde"), allow(unused_variables))]
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        #[cfg(feature = "serde")]
+        let state: State = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, eframe::APP_KEY))
+            .unwrap_or_default();
+        #[cfg(not(feature = "serde"))]
+        let state: State = State::default();
+
+        state.control_loop.start();
+        Self { state }
     }
 
     fn bar_contents(&mut self, ui: &mut egui::Ui, frame: &mut eframe::Frame) {
         egui::widgets::global_dark_light_mode_switch(ui);
 
         ui.separator();
+
+        let control_loop: &ControlLoop = &self.state.control_loop;
+        let (lost, estop, disarmed, mode, turbo_armed): (Vec<Role>, bool, bool, SpeedMode, bool) =
+            control_loop.read(|controller| {
+                let joints = &controller.joints;
+                (
+                    joints.lost.iter().copied().collect(),
+                    joints.estop,
+                    joints.disarmed,
+                    joints.speed_modes.active(),
+                    joints.speed_modes.turbo_armed(),
+                )
+            });
+        for role in lost {
+            ui.label(
+                RichText::new(format!("⚠ {role} controller lost"))
+                    .heading()
+                    .strong()
+                    .color(ui.visuals().error_fg_color),
+            );
+            let connected: bool = control_loop.shared(
+                |controller| &controller.gamepad_control_panel,
+                |panel| panel.holds(role),
+            );
+            if ui
+                .add_enabled(connected, egui::Button::new("Re-acquire"))
+                .on_disabled_hover_text("Reconnect the controller or assign the role to another one.")
+                .clicked()
+            {
+                control_loop.write(|controller| controller.joints.reacquire(role));
+            }
+
+            ui.separator();
+        }
+
+        if estop {
+            ui.label(
+                RichText::new("⛔ E-STOP")
+                    .heading()
+                    .strong()
+                    .color(ui.visuals().error_fg_color),
+            );
+            if ui.button("Reset").clicked() {
+                control_loop.write(|controller| controller.joints.reset_estop());
+            }
+
+            ui.separator();
+        }
+
+        if disarmed {
+            ui.label(
+                RichText::new("🔒 DISARMED")
+                    .heading()
+                    .strong()
+                    .color(ui.visuals().warn_fg_color),
+            );
+            if ui.button("Arm").clicked() {
+                control_loop.write(|controller| controller.joints.rearm());
+            }
+
+            ui.separator();
+        }
+
+        ui.label(
+            RichText::new(mode.to_string())
+                .heading()
+                .strong()
+                .color(mode.color()),
+        );
+        if turbo_armed {
+            ui.label(
+                RichText::new("hold modifier for turbo").color(SpeedMode::Turbo.color()),
+            );
+        }
+
+        ui.separator();
     }
 
     fn gamepad_control_panel_contents(
         &mut self,
         ui: &mut egui::Ui,
     ) {
-        self.state.gamepad_control_panel.ui(ui);
+        let control_loop: &ControlLoop = &self.state.control_loop;
+        let bindings: Bindings = control_loop.read(|controller| controller.joints.bindings.clone());
+        control_loop.shared(
+            |controller| &controller.gamepad_control_panel,
+            |panel| panel.ui(ui, &bindings),
+        );
     }
 
     fn wasm32_info_panel_contents(
@@ -86,13 +301,82 @@ impl HomePage {
         frame: &mut eframe::Frame,
     ) {
         self.state.wasm_info_panel.ui(ui, frame);
+
+        ui.separator();
+
+        ui.vertical_centered(|ui| {
+            ui.heading("⏱ Control Loop");
+        });
+
+        ui.separator();
+
+        self.state.control_loop.ui(ui);
+
+        ui.separator();
+
+        ui.vertical_centered(|ui| {
+            ui.heading("⏺ Recording");
+        });
+
+        ui.separator();
+
+        self.recording_contents(ui);
+    }
+
+    fn recording_contents(&mut self, ui: &mut egui::Ui) {
+        let control_loop: &ControlLoop = &self.state.control_loop;
+        let status: RecorderStatus =
+            control_loop.read(|controller| controller.joints.recorder.status());
+        match status.ui(ui) {
+            // both go through the whole recording, on a copy so the loop isn't held up.
+            #[cfg(feature = "serde")]
+            Some(RecorderRequest::Save) => {
+                if let Some(recording) =
+                    control_loop.read(|controller| controller.joints.recorder.last.clone())
+                {
+                    let report: Result<String, String> = Recorder::save(&recording);
+                    control_loop.write(|controller| controller.joints.recorder.report = Some(report));
+                }
+            }
+            #[cfg(feature = "serde")]
+            Some(RecorderRequest::Verify) => {
+                if let Some(recording) =
+                    control_loop.read(|controller| controller.joints.recorder.last.clone())
+                {
+                    let report: Result<String, String> = JointState::verify(&recording);
+                    control_loop.write(|controller| controller.joints.recorder.report = Some(report));
+                }
+            }
+            Some(request) => control_loop.write(|controller| controller.joints.request(request)),
+            None => (),
+        }
     }
 }
 
 impl eframe::App for HomePage {
+    #[cfg(feature = "serde")]
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, eframe::APP_KEY, &self.state);
+    }
+
     fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
-        self.state.gamepad_control_panel.update(&mut self.state.joints);
         self.state.wasm_info_panel.update(ctx, frame);
+        let control_loop: &ControlLoop = &self.state.control_loop;
+        control_loop.write(|controller| {
+            controller
+                .repainter
+                .frame(ctx, &self.state.wasm_info_panel.repaint, time_now())
+        });
+        control_loop.shared(
+            |controller| &controller.gamepad_control_panel,
+            |panel| panel.frame(ctx),
+        );
+        #[cfg(feature = "serde")]
+        for (name, contents) in file::dropped(ctx) {
+            let recording: Result<Recording, String> =
+                contents.and_then(|contents| Recording::parse(&contents));
+            control_loop.write(|controller| controller.joints.recorder.loaded(&name, recording));
+        }
 
         egui::TopBottomPanel::top("top_p").show(ctx, |ui| {
             ui.horizontal_wrapped(|ui| {
@@ -102,8 +386,12 @@ impl eframe::App for HomePage {
         });
 
         egui::TopBottomPanel::bottom("log")
+            .resizable(true)
+            .default_height(200f32)
             .show(ctx, |ui| {
-                ui.heading("Event Log");
+                self.state
+                    .control_loop
+                    .shared(|controller| &controller.event_log, |event_log| event_log.ui(ui));
             });
 
         egui::SidePanel::left("side_panel_left")
@@ -140,7 +428,6 @@ impl eframe::App for HomePage {
             )
             .show(
                 ctx, |ui| {
-                    let mut added_nodes = Vec::new();
                     DockArea::new(&mut self.state.docks.tree)
                         .draggable_tabs(false)
                         .show_add_popup(false)
@@ -154,17 +441,13 @@ impl eframe::App for HomePage {
                         .show_inside(
                             ui,
                             &mut TabViewer {
-                                added_nodes: &mut added_nodes,
+                                control_loop: &self.state.control_loop,
                             },
                         );
-
-                    added_nodes.drain(..).for_each(|(surface, node)| {
-                        self.state.docks.tree.set_focused_node_and_surface((surface, node));
-                        self.state.docks.tree.push_to_focused_leaf(self.state.docks.counter);
-                        self.state.docks.counter += 1;
-                    });
                 });
 
-        ctx.request_repaint();
+        if let Some(interval) = self.state.wasm_info_panel.repaint.idle_interval() {
+            ctx.request_repaint_after(interval);
+        }
     }
 }
@@ -0,0 +1,8 @@
+pub(crate) mod action;
+pub(crate) mod binding;
+pub(crate) mod event;
+pub(crate) mod filter;
+pub(crate) mod gesture;
+pub(crate) mod role;
+pub(crate) mod snapshot;
+pub(crate) mod source;
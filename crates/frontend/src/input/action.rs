@@ -0,0 +1,205 @@
+use std::fmt::{Display, Formatter};
+
+use crate::command::scheme::DriveScheme;
+use crate::input::role::Role;
+
+// number of arm joints that can be bound.
+pub(crate) const ARM_JOINT_ACTIONS: usize = 6;
+// number of arm pose slots that can be bound.
+pub(crate) const POSE_SLOT_ACTIONS: usize = 4;
+
+/// Something the operator wants to do, independent of the input that triggers it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub(crate) enum Action {
+    // global.
+    EStop,
+    ResetEStop,
+    CycleScheme,
+    Arm,
+    Disarm,
+    // drive base.
+    Drive,
+    Turn,
+    Throttle,
+    Brake,
+    TankLeft,
+    TankRight,
+    SpeedUp,
+    SpeedDown,
+    TurboModifier,
+    ResetOdometry,
+    // manipulator, joint index from the base.
+    ArmJoint(usize),
+    CartesianX,
+    CartesianY,
+    CartesianZ,
+    CartesianRoll,
+    CartesianPitch,
+    CartesianYaw,
+    // recalls the pose assigned to the slot.
+    PoseSlot(usize),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum ActionKind {
+    // bidirectional value in [-1, 1].
+    Axis,
+    // value in [0, 1], pressed from 0.5.
+    Button,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Context {
+    // always active, must not share an input with anything.
+    Global,
+    Drive,
+    Arm,
+}
+
+impl Action {
+    pub(crate) fn all() -> Vec<Action> {
+        let mut actions: Vec<Action> = vec![
+            Action::EStop,
+            Action::ResetEStop,
+            Action::CycleScheme,
+            Action::Arm,
+            Action::Disarm,
+            Action::Drive,
+            Action::Turn,
+            Action::Throttle,
+            Action::Brake,
+            Action::TankLeft,
+            Action::TankRight,
+            Action::SpeedUp,
+            Action::SpeedDown,
+            Action::TurboModifier,
+            Action::ResetOdometry,
+        ];
+        actions.extend((0..ARM_JOINT_ACTIONS).map(Action::ArmJoint));
+        actions.extend([
+            Action::CartesianX,
+            Action::CartesianY,
+            Action::CartesianZ,
+            Action::CartesianRoll,
+            Action::CartesianPitch,
+            Action::CartesianYaw,
+        ]);
+        actions.extend((0..POSE_SLOT_ACTIONS).map(Action::PoseSlot));
+        actions
+    }
+
+    pub(crate) fn kind(&self) -> ActionKind {
+        match self {
+            Action::EStop
+            | Action::ResetEStop
+            | Action::CycleScheme
+            | Action::Arm
+            | Action::Disarm
+            | Action::Throttle
+            | Action::Brake
+            | Action::SpeedUp
+            | Action::SpeedDown
+            | Action::TurboModifier
+            | Action::ResetOdometry
+            | Action::PoseSlot(_) => ActionKind::Button,
+            _ => ActionKind::Axis,
+        }
+    }
+
+    fn context(&self) -> Context {
+        match self {
+            Action::EStop
+            | Action::ResetEStop
+            | Action::CycleScheme
+            | Action::Arm
+            | Action::Disarm => Context::Global,
+            Action::ArmJoint(_)
+            | Action::CartesianX
+            | Action::CartesianY
+            | Action::CartesianZ
+            | Action::CartesianRoll
+            | Action::CartesianPitch
+            | Action::CartesianYaw
+            | Action::PoseSlot(_) => Context::Arm,
+            _ => Context::Drive,
+        }
+    }
+
+    /// Controller role whose inputs trigger this action, `None` for every role.
+    pub(crate) fn role(&self) -> Option<Role> {
+        match self.context() {
+            Context::Global => None,
+            Context::Drive => Some(Role::Driver),
+            Context::Arm => Some(Role::Operator),
+        }
+    }
+
+    /// Drive schemes that read this action, `None` if it is used by all of them.
+    fn schemes(&self) -> Option<&'static [DriveScheme]> {
+        match self {
+            Action::Drive => Some(&[DriveScheme::Arcade]),
+            Action::Turn => Some(&[DriveScheme::Arcade, DriveScheme::Trigger]),
+            Action::Throttle | Action::Brake => Some(&[DriveScheme::Trigger]),
+            Action::TankLeft | Action::TankRight => Some(&[DriveScheme::Tank]),
+            _ => None,
+        }
+    }
+
+    fn is_cartesian(&self) -> bool {
+        self.context() == Context::Arm && !matches!(self, Action::ArmJoint(_) | Action::PoseSlot(_))
+    }
+
+    /// Whether both actions can be active at the same time, so sharing an input is a conflict.
+    pub(crate) fn overlaps(&self, other: &Action) -> bool {
+        if self == other {
+            return false;
+        }
+
+        match (self.context(), other.context()) {
+            (Context::Global, _) | (_, Context::Global) => true,
+            // arm actions are meant for the operator, drive actions for the driver.
+            (Context::Drive, Context::Arm) | (Context::Arm, Context::Drive) => false,
+            // joint and cartesian jogging are separate arm modes, poses recall in both.
+            (Context::Arm, Context::Arm) => {
+                matches!(self, Action::PoseSlot(_))
+                    || matches!(other, Action::PoseSlot(_))
+                    || self.is_cartesian() == other.is_cartesian()
+            }
+            (Context::Drive, Context::Drive) => match (self.schemes(), other.schemes()) {
+                (Some(a), Some(b)) => a.iter().any(|scheme| b.contains(scheme)),
+                _ => true,
+            },
+        }
+    }
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::EStop => write!(f, "E-Stop"),
+            Action::ResetEStop => write!(f, "Reset E-Stop"),
+            Action::CycleScheme => write!(f, "Cycle Scheme"),
+            Action::Arm => write!(f, "Arm"),
+            Action::Disarm => write!(f, "Disarm"),
+            Action::Drive => write!(f, "Drive"),
+            Action::Turn => write!(f, "Turn"),
+            Action::Throttle => write!(f, "Throttle"),
+            Action::Brake => write!(f, "Brake / Reverse"),
+            Action::TankLeft => write!(f, "Tank Left"),
+            Action::TankRight => write!(f, "Tank Right"),
+            Action::SpeedUp => write!(f, "Speed Up"),
+            Action::SpeedDown => write!(f, "Speed Down"),
+            Action::TurboModifier => write!(f, "Turbo Modifier"),
+            Action::ResetOdometry => write!(f, "Reset Odometry"),
+            Action::ArmJoint(index) => write!(f, "Arm Joint {}", index + 1),
+            Action::CartesianX => write!(f, "Cartesian X"),
+            Action::CartesianY => write!(f, "Cartesian Y"),
+            Action::CartesianZ => write!(f, "Cartesian Z"),
+            Action::CartesianRoll => write!(f, "Cartesian Roll"),
+            Action::CartesianPitch => write!(f, "Cartesian Pitch"),
+            Action::CartesianYaw => write!(f, "Cartesian Yaw"),
+            Action::PoseSlot(slot) => write!(f, "Pose Slot {}", slot + 1),
+        }
+    }
+}
@@ -0,0 +1,972 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+use std::time::SystemTime;
+
+use egui::{DragValue, RichText};
+use gilrs::{Axis, Button};
+
+use crate::input::action::{Action, ActionKind};
+use crate::input::event::{InputEvent, InputEventKind};
+use crate::input::gesture::Presses;
+use crate::input::snapshot::InputSnapshot;
+
+// how far an axis has to move away from where it rested to be captured.
+const CAPTURE_THRESHOLD: f32 = 0.6;
+// seconds, starting values when switching a binding to a gesture.
+const LONG_PRESS_DURATION: f32 = 1.0;
+const DOUBLE_TAP_WINDOW: f32 = 0.3;
+
+/// A single physical input, raw codes are used when the gamepad mapping doesn't name it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub(crate) enum Input {
+    Button(Button),
+    Axis(Axis),
+    ButtonCode(u32),
+    AxisCode(u32),
+}
+
+impl Input {
+    fn button(button: Button, code: u32) -> Self {
+        if button == Button::Unknown {
+            Input::ButtonCode(code)
+        } else {
+            Input::Button(button)
+        }
+    }
+
+    pub(crate) fn value(&self, snapshot: &InputSnapshot) -> f32 {
+        match *self {
+            Input::Button(button) => snapshot.button(button),
+            Input::Axis(axis) => snapshot.axis(axis),
+            Input::ButtonCode(code) => snapshot.button_code(code),
+            Input::AxisCode(code) => snapshot.axis_code(code),
+        }
+    }
+
+    fn is_axis(&self) -> bool {
+        matches!(self, Input::Axis(_) | Input::AxisCode(_))
+    }
+}
+
+impl Display for Input {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Input::Button(button) => write!(f, "{button:?}"),
+            Input::Axis(axis) => write!(f, "{axis:?}"),
+            Input::ButtonCode(code) => write!(f, "Button #{code}"),
+            Input::AxisCode(code) => write!(f, "Axis #{code}"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub(crate) enum Binding {
+    Input { input: Input, inverted: bool },
+    // two inputs pushing the action in opposite directions, like a pair of buttons for an axis.
+    Pair { positive: Input, negative: Input },
+    // `input` only counts while `modifier` is held.
+    Chord { modifier: Input, input: Input },
+    // active once `input` was held for `duration` seconds.
+    LongPress { input: Input, duration: f32 },
+    // active while the second of two presses within `window` seconds is held.
+    DoubleTap { input: Input, window: f32 },
+}
+
+impl Binding {
+    fn input(input: Input) -> Self {
+        Binding::Input {
+            input,
+            inverted: false,
+        }
+    }
+
+    fn inverted(input: Input) -> Self {
+        Binding::Input {
+            input,
+            inverted: true,
+        }
+    }
+
+    fn pair(positive: Input, negative: Input) -> Self {
+        Binding::Pair { positive, negative }
+    }
+
+    fn value(&self, snapshot: &InputSnapshot, presses: &Presses, now: SystemTime) -> f32 {
+        match *self {
+            Binding::Input { input, inverted } => {
+                let value: f32 = input.value(snapshot);
+                if inverted {
+                    -value
+                } else {
+                    value
+                }
+            }
+            Binding::Pair { positive, negative } => {
+                positive.value(snapshot) - negative.value(snapshot)
+            }
+            Binding::Chord { modifier, input } => {
+                if modifier.value(snapshot).abs() >= 0.5 {
+                    input.value(snapshot)
+                } else {
+                    0f32
+                }
+            }
+            Binding::LongPress { input, duration } => {
+                f32::from(u8::from(presses.get(input).held_for(now) >= duration))
+            }
+            Binding::DoubleTap { input, window } => {
+                f32::from(u8::from(presses.get(input).double_tapped(window)))
+            }
+        }
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        match *self {
+            Binding::Input { input, .. }
+            | Binding::LongPress { input, .. }
+            | Binding::DoubleTap { input, .. } => vec![input],
+            Binding::Pair { positive, negative } => vec![positive, negative],
+            Binding::Chord { modifier, input } => vec![modifier, input],
+        }
+    }
+
+    /// Inputs whose presses have to be tracked over time.
+    fn gesture_input(&self) -> Option<Input> {
+        match *self {
+            Binding::LongPress { input, .. } | Binding::DoubleTap { input, .. } => Some(input),
+            _ => None,
+        }
+    }
+
+    /// Plain, long-press and double-tap variants of a single button binding.
+    fn gesture_ui(&mut self, ui: &mut egui::Ui, action: Action) {
+        let input: Input = match *self {
+            Binding::Input { input, .. }
+            | Binding::LongPress { input, .. }
+            | Binding::DoubleTap { input, .. } => input,
+            _ => return,
+        };
+
+        let selected: &str = match self {
+            Binding::LongPress { .. } => "Long Press",
+            Binding::DoubleTap { .. } => "Double Tap",
+            _ => "Press",
+        };
+        egui::ComboBox::from_id_source(("binding_gesture", action))
+            .width(90f32)
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                if ui.selectable_label(selected == "Press", "Press").clicked() {
+                    *self = Binding::input(input);
+                }
+                if ui
+                    .selectable_label(selected == "Long Press", "Long Press")
+                    .clicked()
+                    && selected != "Long Press"
+                {
+                    *self = Binding::LongPress {
+                        input,
+                        duration: LONG_PRESS_DURATION,
+                    };
+                }
+                if ui
+                    .selectable_label(selected == "Double Tap", "Double Tap")
+                    .clicked()
+                    && selected != "Double Tap"
+                {
+                    *self = Binding::DoubleTap {
+                        input,
+                        window: DOUBLE_TAP_WINDOW,
+                    };
+                }
+            });
+
+        match self {
+            Binding::LongPress { duration, .. } => {
+                ui.add(
+                    DragValue::new(duration)
+                        .speed(0.05)
+                        .clamp_range(0.1..=10.0)
+                        .suffix(" s"),
+                );
+            }
+            Binding::DoubleTap { window, .. } => {
+                ui.add(
+                    DragValue::new(window)
+                        .speed(0.01)
+                        .clamp_range(0.05..=2.0)
+                        .suffix(" s"),
+                );
+            }
+            _ => (),
+        }
+    }
+}
+
+impl Display for Binding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Binding::Input {
+                input,
+                inverted: false,
+            } => write!(f, "{input}"),
+            Binding::Input {
+                input,
+                inverted: true,
+            } => write!(f, "-{input}"),
+            Binding::Pair { positive, negative } => write!(f, "+{positive} / -{negative}"),
+            Binding::Chord { modifier, input } => write!(f, "{modifier} + {input}"),
+            Binding::LongPress { input, duration } => write!(f, "{input} (hold {duration:.1} s)"),
+            Binding::DoubleTap { input, .. } => write!(f, "{input} ×2"),
+        }
+    }
+}
+
+/// Gamepads of the same model share their bindings.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub(crate) struct ControllerModel {
+    pub(crate) vendor: u16,
+    pub(crate) product: u16,
+}
+
+impl Display for ControllerModel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04x}:{:04x}", self.vendor, self.product)
+    }
+}
+
+/// At most one binding per action, unbound actions are missing.
+pub(crate) type ActionMap = BTreeMap<Action, Binding>;
+
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+struct ModelBindings {
+    model: ControllerModel,
+    actions: ActionMap,
+}
+
+/// Waiting for the operator to press the input that should trigger an action.
+#[derive(Clone, PartialEq)]
+struct Capture {
+    action: Action,
+    // first button of a pair, axis actions bound to buttons need a second one.
+    positive: Option<Input>,
+    // axis values when capturing started, so resting triggers aren't picked up.
+    baseline: Option<BTreeMap<u32, f32>>,
+}
+
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub(crate) struct Bindings {
+    // used by controllers without their own bindings.
+    default: ActionMap,
+    models: Vec<ModelBindings>,
+    // model whose bindings are shown and edited.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    active: Option<ControllerModel>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    capture: Option<Capture>,
+}
+
+/// Evaluation state of the bindings for one controller.
+#[derive(Default)]
+pub(crate) struct BindingState {
+    pub(crate) model: Option<ControllerModel>,
+    // button actions that were pressed on the last update, for edge detection.
+    held: BTreeSet<Action>,
+    presses: Presses,
+    // time of the last update, gestures are evaluated against it.
+    now: Option<SystemTime>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self {
+            default: default_actions(),
+            models: Vec::new(),
+            active: None,
+            capture: None,
+        }
+    }
+}
+
+impl Bindings {
+    fn actions(&self) -> &ActionMap {
+        self.actions_for(self.active)
+    }
+
+    fn actions_for(&self, model: Option<ControllerModel>) -> &ActionMap {
+        model
+            .and_then(|model| self.models.iter().find(|m| m.model == model))
+            .map(|m| &m.actions)
+            .unwrap_or(&self.default)
+    }
+
+    /// Bindings being edited, the active model gets its own copy of the defaults on first edit.
+    fn actions_mut(&mut self) -> &mut ActionMap {
+        let Some(model) = self.active else {
+            return &mut self.default;
+        };
+
+        let index: usize = match self.models.iter().position(|m| m.model == model) {
+            Some(index) => index,
+            None => {
+                self.models.push(ModelBindings {
+                    model,
+                    actions: self.default.clone(),
+                });
+                self.models.len() - 1
+            }
+        };
+        &mut self.models[index].actions
+    }
+
+    /// Value of an action, [-1, 1] for axis actions and [0, 1] for button actions.
+    pub(crate) fn value(
+        &self,
+        action: Action,
+        snapshot: &InputSnapshot,
+        state: &BindingState,
+    ) -> f32 {
+        // inputs only pick a binding while capturing, except for the e-stop.
+        if self.capturing() && action != Action::EStop {
+            return 0f32;
+        }
+
+        let value: f32 = self
+            .actions_for(state.model)
+            .get(&action)
+            .map(|binding| {
+                binding.value(
+                    snapshot,
+                    &state.presses,
+                    state.now.unwrap_or(SystemTime::UNIX_EPOCH),
+                )
+            })
+            .unwrap_or_default();
+        match action.kind() {
+            ActionKind::Axis => value.clamp(-1f32, 1f32),
+            ActionKind::Button => value.clamp(0f32, 1f32),
+        }
+    }
+
+    /// Whether the action is bound to a single axis, so its value is an absolute deflection.
+    pub(crate) fn is_absolute(&self, action: Action, state: &BindingState) -> bool {
+        matches!(
+            self.actions_for(state.model).get(&action),
+            Some(Binding::Input { input, .. }) if input.is_axis()
+        )
+    }
+
+    /// Button actions that were pressed since the last update, long presses need one without new events too.
+    pub(crate) fn update(
+        &self,
+        snapshot: &InputSnapshot,
+        state: &mut BindingState,
+        now: SystemTime,
+    ) -> Vec<Action> {
+        let gesture_inputs: Vec<Input> = self
+            .actions_for(state.model)
+            .values()
+            .filter_map(Binding::gesture_input)
+            .collect();
+        state
+            .presses
+            .update(gesture_inputs.into_iter(), snapshot, now);
+        state.now = Some(now);
+
+        let held: BTreeSet<Action> = Action::all()
+            .into_iter()
+            .filter(|action| action.kind() == ActionKind::Button)
+            .filter(|action| self.value(*action, snapshot, state) >= 0.5)
+            .collect();
+        let pressed: Vec<Action> = held.difference(&state.held).copied().collect();
+        state.held = held;
+        pressed
+    }
+
+    pub(crate) fn capturing(&self) -> bool {
+        self.capture.is_some()
+    }
+
+    pub(crate) fn start_capture(&mut self, action: Action) {
+        self.capture = Some(Capture {
+            action,
+            positive: None,
+            baseline: None,
+        });
+    }
+
+    /// Binds the captured input, `snapshot` must not contain the event yet.
+    pub(crate) fn capture(
+        &mut self,
+        model: Option<ControllerModel>,
+        event: &InputEvent,
+        snapshot: &InputSnapshot,
+    ) {
+        // only controllers of the model being edited are captured.
+        if model != self.active {
+            return;
+        }
+        let Some(capture) = &mut self.capture else {
+            return;
+        };
+
+        let baseline: &BTreeMap<u32, f32> = capture.baseline.get_or_insert_with(|| {
+            snapshot
+                .axes()
+                .map(|(code, sample)| (*code, sample.value))
+                .collect()
+        });
+
+        let action: Action = capture.action;
+        let binding: Binding = match event.kind {
+            InputEventKind::ButtonPressed { button, code } => {
+                let input: Input = Input::button(button, code);
+                // another button that is already held turns the press into a chord.
+                let modifier: Option<Input> = snapshot
+                    .buttons()
+                    .find(|(c, sample)| sample.pressed && **c != code)
+                    .map(|(c, sample)| Input::button(sample.button, *c));
+                match (action.kind(), capture.positive) {
+                    (ActionKind::Button, _) => match modifier {
+                        Some(modifier) => Binding::Chord { modifier, input },
+                        None => Binding::input(input),
+                    },
+                    (ActionKind::Axis, None) => {
+                        capture.positive = Some(input);
+                        return;
+                    }
+                    (ActionKind::Axis, Some(positive)) if positive != input => {
+                        Binding::pair(positive, input)
+                    }
+                    _ => return,
+                }
+            }
+            InputEventKind::AxisChanged { axis, code, value } if capture.positive.is_none() => {
+                let delta: f32 = value - baseline.get(&code).copied().unwrap_or_default();
+                if delta.abs() < CAPTURE_THRESHOLD {
+                    return;
+                }
+                let input: Input = if axis == Axis::Unknown {
+                    Input::AxisCode(code)
+                } else {
+                    Input::Axis(axis)
+                };
+                // the direction it was pushed in becomes positive.
+                if delta < 0f32 {
+                    Binding::inverted(input)
+                } else {
+                    Binding::input(input)
+                }
+            }
+            _ => return,
+        };
+
+        self.actions_mut().insert(action, binding);
+        self.capture = None;
+    }
+
+    /// Actions of a model whose binding reads `input`.
+    pub(crate) fn bound(&self, model: Option<ControllerModel>, input: Input) -> Vec<Action> {
+        self.actions_for(model)
+            .iter()
+            .filter(|(_, binding)| binding.inputs().contains(&input))
+            .map(|(action, _)| *action)
+            .collect()
+    }
+
+    /// Pairs of actions that can be active together but share an input.
+    pub(crate) fn conflicts(&self) -> Vec<(Action, Action)> {
+        let actions: Vec<(&Action, Vec<Input>)> = self
+            .actions()
+            .iter()
+            .map(|(action, binding)| (action, binding.inputs()))
+            .collect();
+
+        let mut conflicts: Vec<(Action, Action)> = Vec::new();
+        for (index, (action, inputs)) in actions.iter().enumerate() {
+            for (other, other_inputs) in &actions[index + 1..] {
+                if action.overlaps(other) && inputs.iter().any(|input| other_inputs.contains(input))
+                {
+                    conflicts.push((**action, **other));
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// Bindings editor, `connected` are the models of the controllers holding a role.
+    pub(crate) fn ui(&mut self, ui: &mut egui::Ui, connected: &[ControllerModel]) {
+        ui.horizontal(|ui| {
+            ui.label("Controller");
+            let mut models: Vec<ControllerModel> = connected.to_vec();
+            for m in &self.models {
+                if !models.contains(&m.model) {
+                    models.push(m.model);
+                }
+            }
+            let name = |model: Option<ControllerModel>| -> String {
+                model.map_or_else(|| "Default".to_string(), |model| model.to_string())
+            };
+            // switching models mid-capture would bind the input to the wrong one.
+            ui.add_enabled_ui(!self.capturing(), |ui| {
+                egui::ComboBox::from_id_source("bindings_model")
+                    .selected_text(name(self.active))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.active, None, name(None));
+                        for model in models {
+                            ui.selectable_value(&mut self.active, Some(model), name(Some(model)));
+                        }
+                    });
+            });
+            match self.active {
+                Some(model) => {
+                    let custom: bool = self.models.iter().any(|m| m.model == model);
+                    ui.label(if custom {
+                        "custom bindings"
+                    } else {
+                        "default bindings"
+                    });
+                    if ui
+                        .add_enabled(custom, egui::Button::new("↺ Reset to Default"))
+                        .clicked()
+                    {
+                        self.models.retain(|m| m.model != model);
+                    }
+                }
+                None => {
+                    ui.label("used by controllers without their own bindings");
+                }
+            }
+        });
+
+        let mut cancelled: bool = false;
+        let mut single: Option<(Action, Input)> = None;
+        if let Some(capture) = &self.capture {
+            ui.horizontal(|ui| {
+                match capture.positive {
+                    None => ui.strong(format!(
+                        "🎯 Press a button or move an axis for {}, hold another button for a chord",
+                        capture.action
+                    )),
+                    Some(positive) => {
+                        if ui.button(format!("Use {positive} only")).clicked() {
+                            single = Some((capture.action, positive));
+                        }
+                        ui.strong(format!(
+                            "🎯 Press the button that moves {} the other way",
+                            capture.action
+                        ))
+                    }
+                };
+                cancelled = ui.button("Cancel").clicked();
+            });
+        }
+        if let Some((action, input)) = single {
+            self.actions_mut().insert(action, Binding::input(input));
+            self.capture = None;
+        }
+        if cancelled {
+            self.capture = None;
+        }
+
+        ui.separator();
+
+        let conflicts: Vec<(Action, Action)> = self.conflicts();
+        let capturing: Option<Action> = self.capture.as_ref().map(|capture| capture.action);
+        let mut rebind: Option<Action> = None;
+        let mut unbind: Option<Action> = None;
+        let mut invert: Option<Action> = None;
+        let mut gesture: Option<(Action, Binding)> = None;
+        egui::Grid::new("bindings_grid")
+            .striped(true)
+            .num_columns(4)
+            .show(ui, |ui| {
+                let actions: &ActionMap = self.actions();
+                for action in Action::all() {
+                    ui.label(action.to_string());
+
+                    let others: Vec<String> = conflicts
+                        .iter()
+                        .filter_map(|(a, b)| match action {
+                            _ if *a == action => Some(b.to_string()),
+                            _ if *b == action => Some(a.to_string()),
+                            _ => None,
+                        })
+                        .collect();
+                    let text: RichText = match actions.get(&action) {
+                        Some(binding) => RichText::new(binding.to_string()).monospace(),
+                        None => RichText::new("Unbound").weak(),
+                    };
+                    let response: egui::Response = if others.is_empty() {
+                        ui.label(text)
+                    } else {
+                        ui.label(text.color(ui.visuals().warn_fg_color))
+                            .on_hover_text(format!("⚠ Shares an input with {}", others.join(", ")))
+                    };
+                    if capturing == Some(action) {
+                        response.highlight();
+                    }
+
+                    ui.horizontal(|ui| {
+                        if let Some(binding) = actions.get(&action) {
+                            if action.kind() == ActionKind::Button {
+                                let mut edited: Binding = *binding;
+                                edited.gesture_ui(ui, action);
+                                if edited != *binding {
+                                    gesture = Some((action, edited));
+                                }
+                            }
+                        }
+                    });
+
+                    ui.horizontal(|ui| {
+                        if ui.small_button("🎯 Rebind").clicked() {
+                            rebind = Some(action);
+                        }
+                        if let Some(Binding::Input { .. }) = actions.get(&action) {
+                            if ui.small_button("⇅ Invert").clicked() {
+                                invert = Some(action);
+                            }
+                        }
+                        if actions.contains_key(&action) && ui.small_button("🗑").clicked() {
+                            unbind = Some(action);
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+
+        if let Some(action) = rebind {
+            self.start_capture(action);
+        }
+        if let Some(action) = invert {
+            if let Some(Binding::Input { inverted, .. }) = self.actions_mut().get_mut(&action) {
+                *inverted = !*inverted;
+            }
+        }
+        if let Some(action) = unbind {
+            self.actions_mut().remove(&action);
+        }
+        if let Some((action, binding)) = gesture {
+            self.actions_mut().insert(action, binding);
+        }
+
+        if !conflicts.is_empty() {
+            ui.separator();
+            for (a, b) in &conflicts {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!("⚠ {a} and {b} share an input"),
+                );
+            }
+        }
+    }
+}
+
+fn default_actions() -> ActionMap {
+    use gilrs::Axis::*;
+    use gilrs::Button::*;
+
+    BTreeMap::from([
+        (Action::EStop, Binding::input(Input::Button(Start))),
+        // clearing an e-stop must be deliberate.
+        (
+            Action::ResetEStop,
+            Binding::LongPress {
+                input: Input::Button(RightThumb),
+                duration: 1.5,
+            },
+        ),
+        (Action::CycleScheme, Binding::input(Input::Button(Select))),
+        // arming and disarming must not follow a stray press either.
+        (
+            Action::Arm,
+            Binding::LongPress {
+                input: Input::Button(Mode),
+                duration: LONG_PRESS_DURATION,
+            },
+        ),
+        (
+            Action::Disarm,
+            Binding::DoubleTap {
+                input: Input::Button(LeftThumb),
+                window: DOUBLE_TAP_WINDOW,
+            },
+        ),
+        (Action::Drive, Binding::input(Input::Axis(LeftStickY))),
+        (Action::Turn, Binding::input(Input::Axis(LeftStickX))),
+        (
+            Action::Throttle,
+            Binding::input(Input::Button(RightTrigger2)),
+        ),
+        (Action::Brake, Binding::input(Input::Button(LeftTrigger2))),
+        (Action::TankLeft, Binding::input(Input::Axis(LeftStickY))),
+        (Action::TankRight, Binding::input(Input::Axis(RightStickY))),
+        (Action::SpeedUp, Binding::input(Input::Button(DPadUp))),
+        (Action::SpeedDown, Binding::input(Input::Button(DPadDown))),
+        (
+            Action::TurboModifier,
+            Binding::input(Input::Button(LeftTrigger)),
+        ),
+        (
+            Action::ResetOdometry,
+            Binding::LongPress {
+                input: Input::Button(North),
+                duration: LONG_PRESS_DURATION,
+            },
+        ),
+        (
+            Action::ArmJoint(0),
+            Binding::input(Input::Axis(RightStickY)),
+        ),
+        (
+            Action::ArmJoint(1),
+            Binding::pair(Input::Button(North), Input::Button(South)),
+        ),
+        (
+            Action::ArmJoint(2),
+            Binding::pair(Input::Button(East), Input::Button(West)),
+        ),
+        // X forward, Y left, Z up.
+        (Action::CartesianX, Binding::input(Input::Axis(LeftStickY))),
+        (
+            Action::CartesianY,
+            Binding::inverted(Input::Axis(LeftStickX)),
+        ),
+        (
+            Action::CartesianZ,
+            Binding::pair(Input::Button(RightTrigger2), Input::Button(LeftTrigger2)),
+        ),
+        (
+            Action::CartesianRoll,
+            Binding::pair(Input::Button(RightTrigger), Input::Button(LeftTrigger)),
+        ),
+        (
+            Action::CartesianPitch,
+            Binding::input(Input::Axis(RightStickY)),
+        ),
+        (
+            Action::CartesianYaw,
+            Binding::inverted(Input::Axis(RightStickX)),
+        ),
+        (Action::PoseSlot(0), Binding::input(Input::Button(DPadLeft))),
+        (
+            Action::PoseSlot(1),
+            Binding::input(Input::Button(DPadRight)),
+        ),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn at(seconds: f64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs_f64(seconds)
+    }
+
+    fn event(kind: InputEventKind) -> InputEvent {
+        InputEvent {
+            time: at(0.0),
+            kind,
+        }
+    }
+
+    fn pressed(button: Button) -> InputEvent {
+        event(InputEventKind::ButtonPressed {
+            button,
+            code: button as u32,
+        })
+    }
+
+    fn set_button(snapshot: &mut InputSnapshot, button: Button, value: f32) {
+        let code: u32 = button as u32;
+        snapshot.update(&event(InputEventKind::ButtonChanged {
+            button,
+            code,
+            value,
+        }));
+    }
+
+    fn pushed(axis: Axis, value: f32) -> InputEvent {
+        event(InputEventKind::AxisChanged {
+            axis,
+            code: axis as u32,
+            value,
+        })
+    }
+
+    #[test]
+    fn pairs_and_inverted_axes() {
+        let bindings: Bindings = Bindings::default();
+        let state: BindingState = BindingState::default();
+        let mut snapshot: InputSnapshot = InputSnapshot::default();
+
+        set_button(&mut snapshot, Button::North, 1.0);
+        assert_eq!(bindings.value(Action::ArmJoint(1), &snapshot, &state), 1.0);
+        set_button(&mut snapshot, Button::South, 1.0);
+        assert_eq!(bindings.value(Action::ArmJoint(1), &snapshot, &state), 0.0);
+
+        snapshot.update(&pushed(Axis::LeftStickX, 0.5));
+        assert_eq!(bindings.value(Action::CartesianY, &snapshot, &state), -0.5);
+    }
+
+    #[test]
+    fn buttons_press_once_while_held() {
+        let bindings: Bindings = Bindings::default();
+        let mut state: BindingState = BindingState::default();
+        let mut snapshot: InputSnapshot = InputSnapshot::default();
+
+        set_button(&mut snapshot, Button::Select, 1.0);
+        assert_eq!(
+            bindings.update(&snapshot, &mut state, at(0.0)),
+            vec![Action::CycleScheme]
+        );
+        assert!(bindings.update(&snapshot, &mut state, at(0.1)).is_empty());
+        set_button(&mut snapshot, Button::Select, 0.0);
+        assert!(bindings.update(&snapshot, &mut state, at(0.2)).is_empty());
+    }
+
+    #[test]
+    fn capture_binds_buttons_and_pairs() {
+        let mut bindings: Bindings = Bindings::default();
+        let snapshot: InputSnapshot = InputSnapshot::default();
+
+        bindings.start_capture(Action::CycleScheme);
+        bindings.capture(None, &pressed(Button::Mode), &snapshot);
+        assert!(!bindings.capturing());
+        assert_eq!(
+            bindings.actions().get(&Action::CycleScheme),
+            Some(&Binding::input(Input::Button(Button::Mode)))
+        );
+
+        // axis actions wait for a second button.
+        bindings.start_capture(Action::Turn);
+        bindings.capture(None, &pressed(Button::East), &snapshot);
+        assert!(bindings.capturing());
+        bindings.capture(None, &pressed(Button::West), &snapshot);
+        assert_eq!(
+            bindings.actions().get(&Action::Turn),
+            Some(&Binding::pair(
+                Input::Button(Button::East),
+                Input::Button(Button::West)
+            ))
+        );
+    }
+
+    #[test]
+    fn capture_ignores_resting_axes() {
+        let mut bindings: Bindings = Bindings::default();
+        let mut snapshot: InputSnapshot = InputSnapshot::default();
+        snapshot.update(&pushed(Axis::RightZ, 0.9));
+
+        bindings.start_capture(Action::Turn);
+        bindings.capture(None, &pushed(Axis::RightZ, 0.95), &snapshot);
+        assert!(bindings.capturing());
+
+        bindings.capture(None, &pushed(Axis::LeftStickX, -0.8), &snapshot);
+        assert!(!bindings.capturing());
+        assert_eq!(
+            bindings.actions().get(&Action::Turn),
+            Some(&Binding::inverted(Input::Axis(Axis::LeftStickX)))
+        );
+    }
+
+    #[test]
+    fn capture_ignores_other_models() {
+        let mut bindings: Bindings = Bindings::default();
+        let snapshot: InputSnapshot = InputSnapshot::default();
+        let other: ControllerModel = ControllerModel {
+            vendor: 0x045e,
+            product: 0x028e,
+        };
+
+        bindings.start_capture(Action::CycleScheme);
+        bindings.capture(Some(other), &pressed(Button::Mode), &snapshot);
+        assert!(bindings.capturing());
+        bindings.capture(None, &pressed(Button::Mode), &snapshot);
+        assert!(!bindings.capturing());
+    }
+
+    #[test]
+    fn capture_turns_a_held_button_into_a_chord() {
+        let mut bindings: Bindings = Bindings::default();
+        let mut snapshot: InputSnapshot = InputSnapshot::default();
+        snapshot.update(&pressed(Button::LeftTrigger));
+
+        bindings.start_capture(Action::SpeedUp);
+        bindings.capture(None, &pressed(Button::North), &snapshot);
+        assert_eq!(
+            bindings.actions().get(&Action::SpeedUp),
+            Some(&Binding::Chord {
+                modifier: Input::Button(Button::LeftTrigger),
+                input: Input::Button(Button::North),
+            })
+        );
+    }
+
+    // changes a button and returns the actions pressed by it.
+    fn tap(
+        bindings: &Bindings,
+        state: &mut BindingState,
+        snapshot: &mut InputSnapshot,
+        (button, value): (Button, f32),
+        seconds: f64,
+    ) -> Vec<Action> {
+        set_button(snapshot, button, value);
+        bindings.update(snapshot, state, at(seconds))
+    }
+
+    #[test]
+    fn long_press_fires_once_after_its_duration() {
+        let bindings: Bindings = Bindings::default();
+        let mut state: BindingState = BindingState::default();
+        let mut snapshot: InputSnapshot = InputSnapshot::default();
+        let thumb = |value: f32| (Button::RightThumb, value);
+
+        assert!(tap(&bindings, &mut state, &mut snapshot, thumb(1.0), 10.0).is_empty());
+        // released too early, the next press starts over.
+        tap(&bindings, &mut state, &mut snapshot, thumb(0.0), 11.0);
+        tap(&bindings, &mut state, &mut snapshot, thumb(1.0), 11.2);
+        assert!(bindings.update(&snapshot, &mut state, at(12.5)).is_empty());
+        assert_eq!(
+            bindings.update(&snapshot, &mut state, at(12.8)),
+            vec![Action::ResetEStop]
+        );
+        assert!(bindings.update(&snapshot, &mut state, at(13.0)).is_empty());
+    }
+
+    #[test]
+    fn double_tap_needs_both_presses_within_the_window() {
+        let mut bindings: Bindings = Bindings::default();
+        bindings.default.insert(
+            Action::CycleScheme,
+            Binding::DoubleTap {
+                input: Input::Button(Button::Select),
+                window: 0.3,
+            },
+        );
+        let mut state: BindingState = BindingState::default();
+        let mut snapshot: InputSnapshot = InputSnapshot::default();
+        let select = |value: f32| (Button::Select, value);
+
+        // too slow.
+        tap(&bindings, &mut state, &mut snapshot, select(1.0), 0.0);
+        tap(&bindings, &mut state, &mut snapshot, select(0.0), 0.1);
+        assert!(tap(&bindings, &mut state, &mut snapshot, select(1.0), 0.5).is_empty());
+        tap(&bindings, &mut state, &mut snapshot, select(0.0), 0.6);
+
+        assert_eq!(
+            tap(&bindings, &mut state, &mut snapshot, select(1.0), 0.75),
+            vec![Action::CycleScheme]
+        );
+    }
+}
@@ -0,0 +1,70 @@
+use std::time::SystemTime;
+
+use gilrs::{Axis, Button, EventType};
+
+/// Gamepad event decoupled from gilrs, so codes can be stored and events produced by other sources.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub(crate) enum InputEventKind {
+    ButtonPressed {
+        button: Button,
+        code: u32,
+    },
+    ButtonReleased {
+        button: Button,
+        code: u32,
+    },
+    ButtonChanged {
+        button: Button,
+        code: u32,
+        value: f32,
+    },
+    AxisChanged {
+        axis: Axis,
+        code: u32,
+        value: f32,
+    },
+    Connected,
+    Disconnected,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub(crate) struct InputEvent {
+    pub(crate) time: SystemTime,
+    pub(crate) kind: InputEventKind,
+}
+
+impl InputEvent {
+    /// `None` for events that carry no input, like dropped or repeated ones.
+    pub(crate) fn from_gilrs(event: &gilrs::Event) -> Option<Self> {
+        let kind: InputEventKind = match event.event {
+            EventType::ButtonPressed(button, code) => InputEventKind::ButtonPressed {
+                button,
+                code: code.into_u32(),
+            },
+            EventType::ButtonReleased(button, code) => InputEventKind::ButtonReleased {
+                button,
+                code: code.into_u32(),
+            },
+            EventType::ButtonChanged(button, value, code) => InputEventKind::ButtonChanged {
+                button,
+                code: code.into_u32(),
+                value,
+            },
+            EventType::AxisChanged(axis, value, code) => InputEventKind::AxisChanged {
+                axis,
+                code: code.into_u32(),
+                value,
+            },
+            EventType::Connected => InputEventKind::Connected,
+            EventType::Disconnected => InputEventKind::Disconnected,
+            EventType::ButtonRepeated(..) | EventType::Dropped => return None,
+        };
+
+        Some(Self {
+            time: event.time,
+            kind,
+        })
+    }
+}
@@ -0,0 +1,406 @@
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+use std::fmt::{Display, Formatter};
+use std::time::SystemTime;
+
+use egui::DragValue;
+use egui_plot::{Legend, Line, PlotPoints};
+use gilrs::Axis;
+
+use crate::gamepad::widgets::AXES;
+use crate::input::role::Role;
+use crate::input::snapshot::InputSnapshot;
+
+// seconds of raw and filtered samples kept for the plot.
+const HISTORY: f64 = 5.0;
+// filtered values this close to a released axis are snapped to it, the decay never gets there.
+const SNAP: f64 = 1e-3;
+
+#[derive(Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub(crate) enum FilterKind {
+    #[default]
+    None,
+    // first-order exponential smoothing.
+    LowPass,
+    MovingAverage,
+    // low-pass whose cutoff rises with speed, smooth at rest and responsive when moving.
+    OneEuro,
+}
+
+impl FilterKind {
+    const ALL: [FilterKind; 4] = [
+        FilterKind::None,
+        FilterKind::LowPass,
+        FilterKind::MovingAverage,
+        FilterKind::OneEuro,
+    ];
+}
+
+impl Display for FilterKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FilterKind::None => write!(f, "None"),
+            FilterKind::LowPass => write!(f, "Low-Pass"),
+            FilterKind::MovingAverage => write!(f, "Moving Average"),
+            FilterKind::OneEuro => write!(f, "One Euro"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub(crate) struct FilterSettings {
+    kind: FilterKind,
+    // Hz, low-pass.
+    cutoff: f64,
+    // samples, moving average.
+    window: usize,
+    // Hz, One Euro cutoff at rest.
+    min_cutoff: f64,
+    // One Euro cutoff increase per unit of speed.
+    beta: f64,
+    // Hz, One Euro speed estimate.
+    derivative_cutoff: f64,
+}
+
+impl Default for FilterSettings {
+    fn default() -> Self {
+        Self {
+            kind: FilterKind::None,
+            cutoff: 5.0,
+            window: 5,
+            min_cutoff: 1.0,
+            beta: 0.5,
+            derivative_cutoff: 1.0,
+        }
+    }
+}
+
+impl FilterSettings {
+    fn ui(&mut self, ui: &mut egui::Ui, axis: Axis) {
+        egui::ComboBox::from_id_source(("filter_kind", format!("{axis:?}")))
+            .selected_text(self.kind.to_string())
+            .show_ui(ui, |ui| {
+                for kind in FilterKind::ALL {
+                    ui.selectable_value(&mut self.kind, kind, kind.to_string());
+                }
+            });
+
+        match self.kind {
+            FilterKind::None => (),
+            FilterKind::LowPass => {
+                ui.add(
+                    DragValue::new(&mut self.cutoff)
+                        .speed(0.1)
+                        .clamp_range(0.1..=100.0)
+                        .prefix("cutoff ")
+                        .suffix(" Hz"),
+                );
+            }
+            FilterKind::MovingAverage => {
+                ui.add(
+                    DragValue::new(&mut self.window)
+                        .clamp_range(1..=100)
+                        .prefix("window ")
+                        .suffix(" samples"),
+                );
+            }
+            FilterKind::OneEuro => {
+                ui.add(
+                    DragValue::new(&mut self.min_cutoff)
+                        .speed(0.05)
+                        .clamp_range(0.01..=50.0)
+                        .prefix("min cutoff ")
+                        .suffix(" Hz"),
+                );
+                ui.add(
+                    DragValue::new(&mut self.beta)
+                        .speed(0.01)
+                        .clamp_range(0.0..=10.0)
+                        .prefix("beta "),
+                );
+                ui.add(
+                    DragValue::new(&mut self.derivative_cutoff)
+                        .speed(0.05)
+                        .clamp_range(0.01..=50.0)
+                        .prefix("d cutoff ")
+                        .suffix(" Hz"),
+                );
+            }
+        }
+    }
+}
+
+/// Smoothing factor of a first-order low-pass sampled every `dt` seconds.
+fn alpha(cutoff: f64, dt: f64) -> f64 {
+    let tau: f64 = 1f64 / (2f64 * PI * cutoff);
+    1f64 / (1f64 + tau / dt)
+}
+
+#[derive(Default)]
+struct FilterState {
+    value: Option<f64>,
+    // raw samples, moving average.
+    samples: VecDeque<f64>,
+    // previous raw sample and filtered speed, One Euro.
+    raw: f64,
+    derivative: f64,
+}
+
+impl FilterState {
+    fn update(&mut self, settings: &FilterSettings, raw: f64, dt: f64) -> f64 {
+        let filtered: f64 = match (settings.kind, self.value) {
+            (FilterKind::MovingAverage, _) => {
+                self.samples.push_back(raw);
+                while self.samples.len() > settings.window.max(1) {
+                    self.samples.pop_front();
+                }
+                self.samples.iter().sum::<f64>() / self.samples.len() as f64
+            }
+            (FilterKind::None, _) | (_, None) => raw,
+            (FilterKind::LowPass, Some(value)) => {
+                value + alpha(settings.cutoff, dt) * (raw - value)
+            }
+            (FilterKind::OneEuro, Some(value)) => {
+                let speed: f64 = (raw - self.raw) / dt;
+                self.derivative +=
+                    alpha(settings.derivative_cutoff, dt) * (speed - self.derivative);
+                let cutoff: f64 = settings.min_cutoff + settings.beta * self.derivative.abs();
+                value + alpha(cutoff, dt) * (raw - value)
+            }
+        };
+        let filtered: f64 = if raw == 0f64 && filtered.abs() < SNAP {
+            0f64
+        } else {
+            filtered
+        };
+
+        if settings.kind != FilterKind::MovingAverage {
+            self.samples.clear();
+        }
+        self.raw = raw;
+        self.value = Some(filtered);
+        filtered
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+struct AxisFilter {
+    axis: Axis,
+    settings: FilterSettings,
+}
+
+/// Filter memory of one controller.
+#[derive(Default)]
+pub(crate) struct FilterStates {
+    states: Vec<(Axis, FilterState)>,
+    last_update: Option<SystemTime>,
+}
+
+/// Smooths named axes before they are mixed into commands.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub(crate) struct AxisFilters {
+    filters: Vec<AxisFilter>,
+    plotted: Axis,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    plotted_role: Role,
+    // seconds since the first update, raw and filtered values of the plotted axis.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    history: VecDeque<[f64; 3]>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    start: Option<SystemTime>,
+}
+
+impl Default for AxisFilters {
+    fn default() -> Self {
+        Self {
+            filters: AXES
+                .iter()
+                .map(|axis| AxisFilter {
+                    axis: *axis,
+                    settings: FilterSettings::default(),
+                })
+                .collect(),
+            plotted: Axis::LeftStickX,
+            plotted_role: Role::Driver,
+            history: VecDeque::new(),
+            start: None,
+        }
+    }
+}
+
+impl AxisFilters {
+    fn settings(&self, axis: Axis) -> FilterSettings {
+        self.filters
+            .iter()
+            .find(|filter| filter.axis == axis)
+            .map(|filter| filter.settings)
+            .unwrap_or_default()
+    }
+
+    /// Filters every named axis of `snapshot` in place, meant to run at a steady rate.
+    pub(crate) fn apply(
+        &mut self,
+        role: Role,
+        states: &mut FilterStates,
+        snapshot: &mut InputSnapshot,
+        now: SystemTime,
+    ) {
+        let dt: f64 = states
+            .last_update
+            .and_then(|last| now.duration_since(last).ok())
+            .map(|elapsed| elapsed.as_secs_f64())
+            .unwrap_or_default();
+        states.last_update = Some(now);
+        if dt <= 0f64 {
+            return;
+        }
+
+        let start: SystemTime = *self.start.get_or_insert(now);
+        let time: f64 = now.duration_since(start).unwrap_or_default().as_secs_f64();
+
+        for axis in AXES {
+            let raw: f64 = snapshot.axis(axis) as f64;
+            let settings: FilterSettings = self.settings(axis);
+            let index: usize = match states.states.iter().position(|(a, _)| *a == axis) {
+                Some(index) => index,
+                None => {
+                    states.states.push((axis, FilterState::default()));
+                    states.states.len() - 1
+                }
+            };
+            let filtered: f64 = states.states[index].1.update(&settings, raw, dt);
+            snapshot.set_axis(axis, filtered as f32);
+
+            if role == self.plotted_role && axis == self.plotted {
+                self.history.push_back([time, raw, filtered]);
+            }
+        }
+
+        while self
+            .history
+            .front()
+            .is_some_and(|[t, _, _]| time - t > HISTORY)
+        {
+            self.history.pop_front();
+        }
+    }
+
+    /// Takes what the UI changed on a copy drawn `before`, the history stays the loop's.
+    pub(crate) fn merge(&mut self, before: &AxisFilters, edited: AxisFilters) {
+        if edited.filters != before.filters {
+            self.filters = edited.filters;
+        }
+        if (edited.plotted_role, edited.plotted) != (before.plotted_role, before.plotted) {
+            self.plotted_role = edited.plotted_role;
+            self.plotted = edited.plotted;
+            self.history.clear();
+        }
+    }
+
+    pub(crate) fn ui(&mut self, ui: &mut egui::Ui) {
+        egui::Grid::new("axis_filters_grid")
+            .striped(true)
+            .num_columns(2)
+            .show(ui, |ui| {
+                for filter in self.filters.iter_mut() {
+                    ui.label(format!("{:?}", filter.axis));
+                    ui.horizontal(|ui| {
+                        filter.settings.ui(ui, filter.axis);
+                    });
+                    ui.end_row();
+                }
+            });
+
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            ui.label("Plot");
+            let previous: (Role, Axis) = (self.plotted_role, self.plotted);
+            egui::ComboBox::from_id_source("filter_plotted_role")
+                .selected_text(self.plotted_role.to_string())
+                .show_ui(ui, |ui| {
+                    for role in Role::ALL {
+                        ui.selectable_value(&mut self.plotted_role, role, role.to_string());
+                    }
+                });
+            egui::ComboBox::from_id_source("filter_plotted_axis")
+                .selected_text(format!("{:?}", self.plotted))
+                .show_ui(ui, |ui| {
+                    for axis in AXES {
+                        ui.selectable_value(&mut self.plotted, axis, format!("{axis:?}"));
+                    }
+                });
+            if (self.plotted_role, self.plotted) != previous {
+                self.history.clear();
+            }
+        });
+
+        let raw: PlotPoints = self.history.iter().map(|[t, r, _]| [*t, *r]).collect();
+        let filtered: PlotPoints = self.history.iter().map(|[t, _, f]| [*t, *f]).collect();
+        egui_plot::Plot::new("axis_filter_plot")
+            .height(200f32)
+            .include_y(1.0)
+            .include_y(-1.0)
+            .legend(Legend::default())
+            .allow_drag(false)
+            .allow_zoom(false)
+            .allow_scroll(false)
+            .show(ui, |plot_ui| {
+                plot_ui.line(Line::new(raw).name("Raw"));
+                plot_ui.line(Line::new(filtered).name("Filtered"));
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(kind: FilterKind, raw: &[f64]) -> Vec<f64> {
+        let settings: FilterSettings = FilterSettings {
+            kind,
+            window: 3,
+            ..FilterSettings::default()
+        };
+        let mut state: FilterState = FilterState::default();
+        raw.iter()
+            .map(|raw| state.update(&settings, *raw, 0.01))
+            .collect()
+    }
+
+    #[test]
+    fn none_passes_through() {
+        assert_eq!(
+            filter(FilterKind::None, &[0.2, -1.0, 0.7]),
+            [0.2, -1.0, 0.7]
+        );
+    }
+
+    #[test]
+    fn moving_average_over_the_window() {
+        assert_eq!(
+            filter(FilterKind::MovingAverage, &[3.0, 0.0, 0.0, 0.0]),
+            [3.0, 1.5, 1.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn smoothing_filters_converge_and_snap_to_a_released_axis() {
+        for kind in [FilterKind::LowPass, FilterKind::OneEuro] {
+            let mut raw: Vec<f64> = vec![1.0; 500];
+            raw.extend([0.0; 500]);
+            let filtered: Vec<f64> = filter(kind, &raw);
+
+            // smoothed, not a step.
+            assert!(filtered[1] == 1.0 && filtered[501] > 0.0);
+            assert!((filtered[499] - 1.0).abs() < SNAP);
+            assert_eq!(filtered[999], 0.0);
+        }
+    }
+}
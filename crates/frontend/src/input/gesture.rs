@@ -0,0 +1,81 @@
+use std::time::SystemTime;
+
+use crate::input::binding::Input;
+use crate::input::snapshot::InputSnapshot;
+
+/// Press history of a single input, used by long-press and double-tap bindings.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct Press {
+    held: bool,
+    // when the current or last press started.
+    start: Option<SystemTime>,
+    // when the press before that started.
+    previous: Option<SystemTime>,
+}
+
+impl Press {
+    fn update(&mut self, pressed: bool, now: SystemTime) {
+        if pressed && !self.held {
+            self.previous = self.start;
+            self.start = Some(now);
+        }
+        self.held = pressed;
+    }
+
+    /// Seconds the input has been held for, zero once released.
+    pub(crate) fn held_for(&self, now: SystemTime) -> f32 {
+        match self.start {
+            Some(start) if self.held => now.duration_since(start).unwrap_or_default().as_secs_f32(),
+            _ => 0f32,
+        }
+    }
+
+    /// Whether the input is held and this press started within `window` seconds of the last one.
+    pub(crate) fn double_tapped(&self, window: f32) -> bool {
+        match (self.start, self.previous) {
+            (Some(start), Some(previous)) if self.held => {
+                start
+                    .duration_since(previous)
+                    .unwrap_or_default()
+                    .as_secs_f32()
+                    <= window
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Presses of every input a gesture binding listens to.
+#[derive(Default)]
+pub(crate) struct Presses {
+    inputs: Vec<(Input, Press)>,
+}
+
+impl Presses {
+    pub(crate) fn update(
+        &mut self,
+        inputs: impl Iterator<Item = Input>,
+        snapshot: &InputSnapshot,
+        now: SystemTime,
+    ) {
+        for input in inputs {
+            let pressed: bool = input.value(snapshot).abs() >= 0.5;
+            match self.inputs.iter_mut().find(|(i, _)| *i == input) {
+                Some((_, press)) => press.update(pressed, now),
+                None => {
+                    let mut press: Press = Press::default();
+                    press.update(pressed, now);
+                    self.inputs.push((input, press));
+                }
+            }
+        }
+    }
+
+    pub(crate) fn get(&self, input: Input) -> Press {
+        self.inputs
+            .iter()
+            .find(|(i, _)| *i == input)
+            .map(|(_, press)| *press)
+            .unwrap_or_default()
+    }
+}
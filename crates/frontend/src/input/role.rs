@@ -0,0 +1,24 @@
+use std::fmt::{Display, Formatter};
+
+/// Part of the robot a controller commands.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub(crate) enum Role {
+    // drive base.
+    Driver,
+    // arm or camera.
+    Operator,
+}
+
+impl Role {
+    pub(crate) const ALL: [Role; 2] = [Role::Driver, Role::Operator];
+}
+
+impl Display for Role {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Role::Driver => write!(f, "🚗 Driver"),
+            Role::Operator => write!(f, "🦾 Operator"),
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use egui::RichText;
+use gilrs::{Axis, Button};
+
+use crate::input::event::{InputEvent, InputEventKind};
+
+#[derive(Clone, Copy)]
+pub(crate) struct ButtonSample {
+    // `Unknown` when the gamepad mapping doesn't name this code.
+    pub(crate) button: Button,
+    pub(crate) value: f32,
+    pub(crate) pressed: bool,
+    pub(crate) time: SystemTime,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct AxisSample {
+    // `Unknown` when the gamepad mapping doesn't name this code.
+    pub(crate) axis: Axis,
+    pub(crate) value: f32,
+    pub(crate) time: SystemTime,
+}
+
+/// Latest state of every button and axis a gamepad reported, keyed by raw event code.
+#[derive(Clone, Default)]
+pub(crate) struct InputSnapshot {
+    buttons: BTreeMap<u32, ButtonSample>,
+    axes: BTreeMap<u32, AxisSample>,
+    // time of the most recent event.
+    time: Option<SystemTime>,
+}
+
+impl InputSnapshot {
+    pub(crate) fn update(&mut self, event: &InputEvent) {
+        let time: SystemTime = event.time;
+        match event.kind {
+            InputEventKind::ButtonPressed { button, code } => {
+                let sample: &mut ButtonSample = self.button_entry(button, code, time);
+                sample.pressed = true;
+                sample.time = time;
+            }
+            InputEventKind::ButtonReleased { button, code } => {
+                let sample: &mut ButtonSample = self.button_entry(button, code, time);
+                sample.pressed = false;
+                sample.time = time;
+            }
+            InputEventKind::ButtonChanged {
+                button,
+                code,
+                value,
+            } => {
+                let sample: &mut ButtonSample = self.button_entry(button, code, time);
+                sample.value = value;
+                sample.time = time;
+            }
+            InputEventKind::AxisChanged { axis, code, value } => {
+                self.axes.insert(code, AxisSample { axis, value, time });
+            }
+            InputEventKind::Connected | InputEventKind::Disconnected => (),
+        }
+        self.time = Some(time);
+    }
+
+    fn button_entry(&mut self, button: Button, code: u32, time: SystemTime) -> &mut ButtonSample {
+        self.buttons.entry(code).or_insert(ButtonSample {
+            button,
+            value: 0f32,
+            pressed: false,
+            time,
+        })
+    }
+
+    /// Value of a mapped button in [0, 1], zero if it was never reported.
+    pub(crate) fn button(&self, button: Button) -> f32 {
+        self.buttons
+            .values()
+            .filter(|sample| sample.button == button)
+            .map(|sample| sample.value)
+            .fold(0f32, f32::max)
+    }
+
+    /// Value of a mapped axis in [-1, 1], zero if it was never reported.
+    pub(crate) fn axis(&self, axis: Axis) -> f32 {
+        self.axes
+            .values()
+            .find(|sample| sample.axis == axis)
+            .map(|sample| sample.value)
+            .unwrap_or_default()
+    }
+
+    /// Overrides the value of every axis mapped to `axis`, used by filters.
+    pub(crate) fn set_axis(&mut self, axis: Axis, value: f32) {
+        for sample in self.axes.values_mut().filter(|sample| sample.axis == axis) {
+            sample.value = value;
+        }
+    }
+
+    /// Value of a button by raw code, for inputs the mapping doesn't name.
+    pub(crate) fn button_code(&self, code: u32) -> f32 {
+        self.buttons
+            .get(&code)
+            .map(|sample| sample.value)
+            .unwrap_or_default()
+    }
+
+    /// Value of an axis by raw code, for inputs the mapping doesn't name.
+    pub(crate) fn axis_code(&self, code: u32) -> f32 {
+        self.axes
+            .get(&code)
+            .map(|sample| sample.value)
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn buttons(&self) -> impl Iterator<Item = (&u32, &ButtonSample)> {
+        self.buttons.iter()
+    }
+
+    pub(crate) fn axes(&self) -> impl Iterator<Item = (&u32, &AxisSample)> {
+        self.axes.iter()
+    }
+
+    pub(crate) fn time(&self) -> Option<SystemTime> {
+        self.time
+    }
+
+    pub(crate) fn ui(&self, ui: &mut egui::Ui) {
+        ui.label(format!(
+            "Last event: {} ms",
+            self.time()
+                .map(millis)
+                .map(|millis| millis.to_string())
+                .unwrap_or_else(|| "never".to_string())
+        ));
+
+        egui::Grid::new("input_snapshot_grid")
+            .striped(true)
+            .num_columns(4)
+            .show(ui, |ui| {
+                ui.strong("Code");
+                ui.strong("Name");
+                ui.strong("Value");
+                ui.strong("Time (ms)");
+                ui.end_row();
+
+                for (code, sample) in self.axes() {
+                    ui.monospace(code.to_string());
+                    ui.monospace(format!("{:?}", sample.axis));
+                    ui.monospace(format!("{:+.4}", sample.value));
+                    ui.monospace(millis(sample.time).to_string());
+                    ui.end_row();
+                }
+
+                for (code, sample) in self.buttons() {
+                    ui.monospace(code.to_string());
+                    ui.monospace(format!("{:?}", sample.button));
+                    let text: RichText =
+                        RichText::new(format!("{:.4} {}", sample.value, sample.pressed))
+                            .monospace();
+                    ui.label(if sample.pressed { text.strong() } else { text });
+                    ui.monospace(millis(sample.time).to_string());
+                    ui.end_row();
+                }
+            });
+    }
+}
+
+fn millis(time: SystemTime) -> u128 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn at(millis: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(millis)
+    }
+
+    fn update(snapshot: &mut InputSnapshot, kind: InputEventKind, millis: u64) {
+        snapshot.update(&InputEvent {
+            time: at(millis),
+            kind,
+        });
+    }
+
+    fn changed(button: Button, code: u32, value: f32) -> InputEventKind {
+        InputEventKind::ButtonChanged {
+            button,
+            code,
+            value,
+        }
+    }
+
+    #[test]
+    fn keeps_the_latest_value_of_every_code() {
+        let mut snapshot: InputSnapshot = InputSnapshot::default();
+        assert_eq!(snapshot.time(), None);
+
+        let (axis, button): (Axis, Button) = (Axis::LeftStickX, Button::South);
+        for (value, millis) in [(0.3, 10), (-0.7, 20)] {
+            let kind: InputEventKind = InputEventKind::AxisChanged {
+                axis,
+                code: 1,
+                value,
+            };
+            update(&mut snapshot, kind, millis);
+        }
+        update(
+            &mut snapshot,
+            InputEventKind::ButtonPressed { button, code: 2 },
+            30,
+        );
+        update(&mut snapshot, changed(button, 2, 1.0), 30);
+
+        assert_eq!(snapshot.axis(axis), -0.7);
+        assert_eq!(snapshot.axes().count(), 1);
+        assert_eq!(snapshot.button(button), 1.0);
+        assert!(snapshot.buttons().all(|(_, sample)| sample.pressed));
+        assert_eq!(snapshot.time(), Some(at(30)));
+
+        update(
+            &mut snapshot,
+            InputEventKind::ButtonReleased { button, code: 2 },
+            40,
+        );
+        assert!(snapshot.buttons().all(|(_, sample)| !sample.pressed));
+        assert_eq!(snapshot.axis(Axis::RightStickX), 0.0);
+    }
+
+    #[test]
+    fn unmapped_codes_are_kept_apart() {
+        let mut snapshot: InputSnapshot = InputSnapshot::default();
+        update(&mut snapshot, changed(Button::Unknown, 7, 1.0), 0);
+        update(&mut snapshot, changed(Button::Unknown, 8, 0.5), 0);
+
+        let codes: Vec<u32> = snapshot.buttons().map(|(code, _)| *code).collect();
+        assert_eq!(codes, [7, 8]);
+        assert_eq!(snapshot.button(Button::South), 0.0);
+    }
+}
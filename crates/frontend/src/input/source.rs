@@ -0,0 +1,43 @@
+use std::time::SystemTime;
+
+use gilrs::GamepadId;
+
+use crate::input::binding::ControllerModel;
+use crate::input::event::InputEvent;
+use crate::input::role::Role;
+
+/// Something that can hold a role.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Device {
+    Gamepad(GamepadId),
+    Keyboard,
+    Touch,
+    Scripted,
+    // controller of a replayed recording, holding the roles it held while recording.
+    Replayed { driver: bool, operator: bool },
+}
+
+impl Device {
+    pub(crate) fn replayed(roles: &[Role]) -> Self {
+        Device::Replayed {
+            driver: roles.contains(&Role::Driver),
+            operator: roles.contains(&Role::Operator),
+        }
+    }
+}
+
+pub(crate) struct SourceEvent {
+    pub(crate) device: Device,
+    // picks the bindings and calibration, `None` for virtual devices.
+    pub(crate) model: Option<ControllerModel>,
+    pub(crate) event: InputEvent,
+}
+
+/// Produces gamepad events, real or synthetic, for the control loop.
+pub(crate) trait InputSource: Send {
+    /// Events since the last call, in order, synthetic sources play them at the loop's `now`.
+    fn poll(&mut self, now: SystemTime) -> Vec<SourceEvent>;
+
+    /// Devices currently available and their names, for the gamepad list.
+    fn devices(&self) -> Vec<(Device, String)>;
+}
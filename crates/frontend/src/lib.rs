@@ -4,3 +4,7 @@ mod home;
 mod gamepad;
 mod wasm;
 mod command;
+mod input;
+mod robot;
+mod file;
+mod event_log;
@@ -0,0 +1,313 @@
+use std::f64::consts::FRAC_PI_2;
+use std::fmt::{Display, Formatter};
+
+use egui::DragValue;
+
+/// Physical description of the robot being driven.
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub(crate) struct RobotProfile {
+    pub(crate) name: String,
+    pub(crate) drive: DriveProfile,
+    // manipulator joints, ordered from the base to the tool.
+    pub(crate) joints: Vec<JointProfile>,
+    // named arm poses, positions ordered like `joints`.
+    pub(crate) poses: Vec<JointPose>,
+}
+
+impl Default for RobotProfile {
+    fn default() -> Self {
+        Self {
+            name: "Default Robot".to_string(),
+            drive: DriveProfile::default(),
+            joints: vec![
+                JointProfile::revolute("Shoulder", -FRAC_PI_2, FRAC_PI_2, 1.0, 0.4),
+                JointProfile::revolute("Elbow", -2.5, 2.5, 1.0, 0.35),
+                JointProfile::revolute("Wrist", -FRAC_PI_2, FRAC_PI_2, 2.0, 0.1),
+            ],
+            poses: vec![
+                JointPose {
+                    name: "Stow".to_string(),
+                    positions: vec![-1.5, 2.4, 0.0],
+                    slot: Some(0),
+                },
+                JointPose {
+                    name: "Ready".to_string(),
+                    positions: vec![0.0, 0.0, 0.0],
+                    slot: Some(1),
+                },
+            ],
+        }
+    }
+}
+
+impl RobotProfile {
+    pub(crate) fn ui(&mut self, ui: &mut egui::Ui) {
+        egui::Grid::new("robot_profile_grid")
+            .striped(true)
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("Name");
+                ui.text_edit_singleline(&mut self.name);
+                ui.end_row();
+
+                self.drive.grid_rows(ui);
+            });
+
+        ui.separator();
+
+        ui.heading("🦾 Joints");
+
+        let mut removed: Option<usize> = None;
+        egui::Grid::new("robot_profile_joints_grid")
+            .striped(true)
+            .num_columns(10)
+            .show(ui, |ui| {
+                ui.strong("Name");
+                ui.strong("Kind");
+                ui.strong("Min");
+                ui.strong("Max");
+                ui.strong("Max Velocity");
+                ui.strong("DH a");
+                ui.strong("DH α");
+                ui.strong("DH d");
+                ui.strong("DH θ");
+                ui.label("");
+                ui.end_row();
+
+                for (index, joint) in self.joints.iter_mut().enumerate() {
+                    joint.grid_row(ui, index);
+                    if ui.small_button("🗑").clicked() {
+                        removed = Some(index);
+                    }
+                    ui.end_row();
+                }
+            });
+
+        if let Some(index) = removed {
+            self.joints.remove(index);
+        }
+
+        if ui.button("➕ Add Joint").clicked() {
+            let name: String = format!("Joint {}", self.joints.len() + 1);
+            self.joints.push(JointProfile::revolute(
+                &name, -FRAC_PI_2, FRAC_PI_2, 1.0, 0.1,
+            ));
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub(crate) enum JointKind {
+    // rad.
+    #[default]
+    Revolute,
+    // m.
+    Prismatic,
+}
+
+impl JointKind {
+    pub(crate) fn unit(&self) -> &'static str {
+        match self {
+            JointKind::Revolute => "rad",
+            JointKind::Prismatic => "m",
+        }
+    }
+}
+
+impl Display for JointKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JointKind::Revolute => write!(f, "Revolute"),
+            JointKind::Prismatic => write!(f, "Prismatic"),
+        }
+    }
+}
+
+/// Arm configuration that can be captured and recalled.
+#[derive(Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub(crate) struct JointPose {
+    pub(crate) name: String,
+    pub(crate) positions: Vec<f64>,
+    // pose slot action that recalls the pose.
+    pub(crate) slot: Option<usize>,
+}
+
+/// Denavit-Hartenberg parameters of the link following a joint.
+#[derive(Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub(crate) struct DhParameters {
+    // m, link length along the common normal.
+    pub(crate) a: f64,
+    // rad, link twist around the common normal.
+    pub(crate) alpha: f64,
+    // m, link offset along the joint axis.
+    pub(crate) d: f64,
+    // rad, angle added to revolute positions or fixed angle of prismatic joints.
+    pub(crate) theta_offset: f64,
+}
+
+/// Soft limits and geometry of a single manipulator joint, positions in the joint's own unit.
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub(crate) struct JointProfile {
+    pub(crate) name: String,
+    pub(crate) kind: JointKind,
+    pub(crate) min_position: f64,
+    pub(crate) max_position: f64,
+    // unit per second.
+    pub(crate) max_velocity: f64,
+    pub(crate) dh: DhParameters,
+}
+
+impl Default for JointProfile {
+    fn default() -> Self {
+        Self::revolute("Joint", -FRAC_PI_2, FRAC_PI_2, 1.0, 0.1)
+    }
+}
+
+impl JointProfile {
+    fn revolute(
+        name: &str,
+        min_position: f64,
+        max_position: f64,
+        max_velocity: f64,
+        length: f64,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            kind: JointKind::Revolute,
+            min_position,
+            max_position,
+            max_velocity,
+            dh: DhParameters {
+                a: length,
+                ..Default::default()
+            },
+        }
+    }
+
+    pub(crate) fn clamp(&self, position: f64) -> f64 {
+        position.clamp(
+            self.min_position.min(self.max_position),
+            self.max_position.max(self.min_position),
+        )
+    }
+
+    fn grid_row(&mut self, ui: &mut egui::Ui, index: usize) {
+        ui.add(egui::TextEdit::singleline(&mut self.name).desired_width(100f32));
+
+        egui::ComboBox::from_id_source(("joint_kind", index))
+            .selected_text(self.kind.to_string())
+            .show_ui(ui, |ui| {
+                for kind in [JointKind::Revolute, JointKind::Prismatic] {
+                    ui.selectable_value(&mut self.kind, kind, kind.to_string());
+                }
+            });
+
+        let unit: &str = self.kind.unit();
+        ui.add(
+            DragValue::new(&mut self.min_position)
+                .speed(0.01)
+                .clamp_range(f64::MIN..=self.max_position)
+                .suffix(format!(" {unit}")),
+        );
+        ui.add(
+            DragValue::new(&mut self.max_position)
+                .speed(0.01)
+                .clamp_range(self.min_position..=f64::MAX)
+                .suffix(format!(" {unit}")),
+        );
+        ui.add(
+            DragValue::new(&mut self.max_velocity)
+                .speed(0.01)
+                .clamp_range(0.0..=f64::MAX)
+                .suffix(format!(" {unit}/s")),
+        );
+
+        ui.add(DragValue::new(&mut self.dh.a).speed(0.001).suffix(" m"));
+        ui.add(
+            DragValue::new(&mut self.dh.alpha)
+                .speed(0.01)
+                .suffix(" rad"),
+        );
+        ui.add(DragValue::new(&mut self.dh.d).speed(0.001).suffix(" m"));
+        ui.add(
+            DragValue::new(&mut self.dh.theta_offset)
+                .speed(0.01)
+                .suffix(" rad"),
+        );
+    }
+}
+
+/// Limits and geometry of a differential (wheeled or tracked) drive base.
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub(crate) struct DriveProfile {
+    // m/s.
+    pub(crate) max_linear_speed: f64,
+    // rad/s.
+    pub(crate) max_angular_speed: f64,
+    // m.
+    pub(crate) wheel_radius: f64,
+    // m, distance between the left and right wheel contact points.
+    pub(crate) track_width: f64,
+}
+
+impl Default for DriveProfile {
+    fn default() -> Self {
+        Self {
+            max_linear_speed: 1.0,
+            max_angular_speed: 2.0,
+            wheel_radius: 0.1,
+            track_width: 0.5,
+        }
+    }
+}
+
+impl DriveProfile {
+    fn grid_rows(&mut self, ui: &mut egui::Ui) {
+        ui.label("Max Linear Speed");
+        ui.add(
+            DragValue::new(&mut self.max_linear_speed)
+                .speed(0.01)
+                .clamp_range(0.0..=f64::MAX)
+                .suffix(" m/s"),
+        );
+        ui.end_row();
+
+        ui.label("Max Angular Speed");
+        ui.add(
+            DragValue::new(&mut self.max_angular_speed)
+                .speed(0.01)
+                .clamp_range(0.0..=f64::MAX)
+                .suffix(" rad/s"),
+        );
+        ui.end_row();
+
+        ui.label("Wheel Radius");
+        ui.add(
+            DragValue::new(&mut self.wheel_radius)
+                .speed(0.001)
+                .clamp_range(0.001..=f64::MAX)
+                .suffix(" m"),
+        );
+        ui.end_row();
+
+        ui.label("Track Width");
+        ui.add(
+            DragValue::new(&mut self.track_width)
+                .speed(0.001)
+                .clamp_range(0.001..=f64::MAX)
+                .suffix(" m"),
+        );
+        ui.end_row();
+    }
+}
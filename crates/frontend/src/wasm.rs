@@ -1,2 +1,3 @@
 pub(crate) mod info_panel;
 pub(crate) mod frame_history;
+pub(crate) mod repaint;
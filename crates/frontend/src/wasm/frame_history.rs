@@ -32,7 +32,13 @@ impl FrameHistory {
         self.frame_times.average().unwrap_or_default()
     }
 
+    /// Frames actually drawn per second, lower than the display rate when idle.
+    pub(super) fn fps(&self) -> f32 {
+        self.frame_times.rate().unwrap_or_default()
+    }
+
     pub(super) fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.label(format!("Effective FPS: {:.1}", self.fps()));
         ui.label(
             format!(
                 "Mean CPU usage: {:.2} ms / frame",
@@ -1,12 +1,15 @@
 use eframe::wgpu;
 use eframe::wgpu::AdapterInfo;
 
+use crate::wasm::repaint::RepaintSettings;
+
 #[derive(Default)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[cfg_attr(feature = "serde", serde(default))]
 pub(crate) struct WasmInfoPanel {
     #[cfg_attr(feature = "serde", serde(skip))]
     frame_history: super::frame_history::FrameHistory,
+    pub(crate) repaint: RepaintSettings,
 }
 
 impl WasmInfoPanel {
@@ -21,6 +24,7 @@ impl WasmInfoPanel {
         ui.separator();
 
         self.frame_history.ui(ui);
+        self.repaint.ui(ui);
     }
 }
 
@@ -0,0 +1,96 @@
+use std::time::{Duration, SystemTime};
+
+use egui::DragValue;
+
+// cap while power saving, regardless of the configured one.
+const POWER_SAVING_FPS: f32 = 15.0;
+// redraw this often without new input so statistics stay fresh, not while power saving.
+const IDLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often the UI is redrawn, frames are only drawn for input, new commands or animations.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub(crate) struct RepaintSettings {
+    max_fps: f32,
+    power_saving: bool,
+}
+
+impl Default for RepaintSettings {
+    fn default() -> Self {
+        Self {
+            max_fps: 60.0,
+            power_saving: false,
+        }
+    }
+}
+
+impl RepaintSettings {
+    fn min_interval(&self) -> Duration {
+        let fps: f32 = if self.power_saving {
+            self.max_fps.min(POWER_SAVING_FPS)
+        } else {
+            self.max_fps
+        };
+        Duration::from_secs_f32(1f32 / fps.max(1f32))
+    }
+
+    pub(crate) fn idle_interval(&self) -> Option<Duration> {
+        (!self.power_saving).then_some(IDLE_INTERVAL)
+    }
+
+    pub(crate) fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Max FPS");
+            ui.add(
+                DragValue::new(&mut self.max_fps)
+                    .speed(1.0)
+                    .clamp_range(1.0..=240.0),
+            );
+            ui.checkbox(&mut self.power_saving, "🔋 Power Saving")
+                .on_hover_text(format!(
+                    "Caps redraws at {POWER_SAVING_FPS} FPS and stops refreshing while idle."
+                ));
+        });
+    }
+}
+
+/// Lets the control loop request redraws, at most as often as the settings allow.
+#[derive(Default)]
+pub(crate) struct Repainter {
+    ctx: Option<egui::Context>,
+    min_interval: Duration,
+    last_frame: Option<SystemTime>,
+}
+
+impl Repainter {
+    /// Called at the start of every frame.
+    pub(crate) fn frame(
+        &mut self,
+        ctx: &egui::Context,
+        settings: &RepaintSettings,
+        now: SystemTime,
+    ) {
+        if self.ctx.is_none() {
+            self.ctx = Some(ctx.clone());
+        }
+        self.min_interval = settings.min_interval();
+        self.last_frame = Some(now);
+    }
+
+    pub(crate) fn request(&self, now: SystemTime) {
+        let Some(ctx) = &self.ctx else {
+            return;
+        };
+
+        let elapsed: Duration = self
+            .last_frame
+            .and_then(|last| now.duration_since(last).ok())
+            .unwrap_or(self.min_interval);
+        if elapsed >= self.min_interval {
+            ctx.request_repaint();
+        } else {
+            ctx.request_repaint_after(self.min_interval - elapsed);
+        }
+    }
+}